@@ -0,0 +1,385 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::neovim_nightly::{
+    bundle::{export_bundle, import_bundle},
+    channel::Channel,
+    config::Config,
+    history::{self, HistoryAction},
+    lock::InstallLock,
+    plan::Plan,
+    store::VersionStore,
+    uninstall::{NvimDir, UninstallPlan},
+    update::{
+        choose_release, install_file, plan_install_file, plan_update, update_neovim, FileChecksum,
+        InstallOutcome,
+    },
+    version::NvimVersion,
+    Result,
+};
+
+#[derive(Parser)]
+#[command(name = "neviraller", version, about = "NEVIRAIDE installer")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Install Neovim from a release, or from a local AppImage or tarball
+    Install {
+        /// Release channel: nightly, stable or a tag such as v0.10.2
+        #[arg(long, default_value = "nightly")]
+        channel: Channel,
+        /// Install this AppImage or tarball instead of downloading one
+        #[arg(long, value_name = "PATH", conflicts_with = "channel")]
+        file: Option<PathBuf>,
+        /// Expected SHA-256 of --file, when no checksum file sits next to it
+        #[arg(
+            long,
+            value_name = "HEX",
+            requires = "file",
+            conflicts_with = "no_verify"
+        )]
+        sha256: Option<String>,
+        /// Install --file without checking its SHA-256
+        #[arg(long, requires = "file")]
+        no_verify: bool,
+        /// Only print what the install would do
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Switch back to the Neovim version that was active before the last switch
+    Rollback {
+        /// Only print what the rollback would do
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create or apply offline bundles for machines without network access
+    Bundle {
+        #[command(subcommand)]
+        command: BundleCommand,
+    },
+    /// Show what neviraller has installed, switched and removed
+    History {
+        /// Only show the most recent entries
+        #[arg(long, short = 'n', value_name = "COUNT")]
+        limit: Option<usize>,
+    },
+    /// Remove Neovim and everything neviraller installed
+    Uninstall {
+        /// Only list what would be removed
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+        /// Also remove Neovim's config directory (~/.config/nvim)
+        #[arg(long)]
+        remove_config: bool,
+        /// Also remove Neovim's data directory, including plugins (~/.local/share/nvim)
+        #[arg(long)]
+        remove_data: bool,
+        /// Also remove Neovim's state directory (~/.local/state/nvim)
+        #[arg(long)]
+        remove_state: bool,
+        /// Also remove Neovim's cache directory (~/.cache/nvim)
+        #[arg(long)]
+        remove_cache: bool,
+    },
+    /// Manage Neovim versions installed side by side
+    Versions {
+        #[command(subcommand)]
+        command: VersionsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Download a release and pack it with the Neovim config and plugins
+    Export {
+        /// Release channel: nightly, stable or a tag such as v0.10.2
+        #[arg(long, default_value = "nightly")]
+        channel: Channel,
+        /// Bundle file to write; defaults to neviraller-bundle-<version>.tar.gz
+        #[arg(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Install Neovim and restore the config and plugins from a bundle
+    Import {
+        bundle: PathBuf,
+        /// Only install Neovim, keep the current config and plugins
+        #[arg(long)]
+        skip_config: bool,
+        /// Import a bundle made for another OS or architecture
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VersionsCommand {
+    /// List installed versions
+    List,
+    /// Switch to an installed version
+    Use { version: NvimVersion },
+    /// Remove an installed version
+    Remove { version: NvimVersion },
+}
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Install {
+            channel,
+            file,
+            sha256,
+            no_verify,
+            dry_run,
+        } => {
+            let verify = match sha256 {
+                Some(sha256) => FileChecksum::Sha256(sha256),
+                None if no_verify => FileChecksum::Unverified,
+                None => FileChecksum::Sidecar,
+            };
+            install(channel, file, &verify, dry_run).await
+        }
+        Command::Rollback { dry_run } => rollback(dry_run),
+        Command::Bundle { command } => bundle(command).await,
+        Command::History { limit } => print_history(limit),
+        Command::Uninstall {
+            dry_run,
+            yes,
+            remove_config,
+            remove_data,
+            remove_state,
+            remove_cache,
+        } => {
+            let nvim_dirs: Vec<NvimDir> = [
+                (remove_config, NvimDir::Config),
+                (remove_data, NvimDir::Data),
+                (remove_state, NvimDir::State),
+                (remove_cache, NvimDir::Cache),
+            ]
+            .into_iter()
+            .filter_map(|(remove, dir)| remove.then_some(dir))
+            .collect();
+            uninstall(&nvim_dirs, dry_run, yes)
+        }
+        Command::Versions { command } => versions(command),
+    }
+}
+
+fn print_history(limit: Option<usize>) -> Result<()> {
+    let entries = history::load()?;
+    if entries.is_empty() {
+        println!("No history recorded yet");
+    }
+
+    let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+    for entry in &entries[skip..] {
+        println!("{}", entry.summary());
+        if let Some(path) = &entry.path {
+            println!("    path:   {}", path.display());
+        }
+        if let Some(sha256) = &entry.sha256 {
+            println!("    sha256: {}", sha256);
+        }
+    }
+
+    Ok(())
+}
+
+fn uninstall(nvim_dirs: &[NvimDir], dry_run: bool, yes: bool) -> Result<()> {
+    let plan = UninstallPlan::new(&Config::load()?, nvim_dirs)?;
+    if plan.is_empty() {
+        println!("Nothing to uninstall");
+        return Ok(());
+    }
+
+    print_plan(&plan.plan());
+    if dry_run {
+        return Ok(());
+    }
+    let _lock = InstallLock::acquire()?;
+
+    if !yes {
+        println!("Proceed? (yes/no)");
+        let mut user_input = String::new();
+        io::stdout().flush()?;
+        io::stdin().read_line(&mut user_input)?;
+        if !matches!(user_input.trim().to_lowercase().as_str(), "yes" | "y") {
+            println!("Uninstall cancelled.");
+            return Ok(());
+        }
+    }
+
+    plan.run()?;
+    println!("Neovim has been uninstalled");
+    Ok(())
+}
+
+async fn bundle(command: BundleCommand) -> Result<()> {
+    let config = Config::load()?;
+    match command {
+        BundleCommand::Export { channel, output } => {
+            let staged = std::env::temp_dir()
+                .join(format!("neviraller-bundle-{}.tar.gz", std::process::id()));
+            let manifest = export_bundle(&channel, &config, &staged, None).await?;
+            let output = output.unwrap_or_else(|| {
+                PathBuf::from(format!("neviraller-bundle-{}.tar.gz", manifest.version))
+            });
+            if std::fs::rename(&staged, &output).is_err() {
+                std::fs::copy(&staged, &output)?;
+                std::fs::remove_file(&staged)?;
+            }
+
+            println!(
+                "Bundled Neovim {} ({}) into {}",
+                manifest.version,
+                manifest.artifact,
+                output.display()
+            );
+            if !manifest.config {
+                println!("Warning: no Neovim config found, the bundle has none");
+            }
+            if !manifest.plugins {
+                println!("Warning: no plugins found, the bundle has none");
+            }
+        }
+        BundleCommand::Import {
+            bundle,
+            skip_config,
+            force,
+        } => {
+            let _lock = InstallLock::acquire()?;
+            let imported = import_bundle(&bundle, &config, !skip_config, force).await?;
+            println!(
+                "Imported the {} bundle created at {}",
+                imported.manifest.tag, imported.manifest.created_at
+            );
+            print_outcome(&imported.install);
+            for backup in &imported.backups {
+                println!("Moved the previous files to {}", backup.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn install(
+    channel: Channel,
+    file: Option<PathBuf>,
+    verify: &FileChecksum,
+    dry_run: bool,
+) -> Result<()> {
+    let config = Config::load()?;
+    if dry_run {
+        let plan = match &file {
+            Some(file) => plan_install_file(file, verify, &config).await?,
+            None => plan_update(&choose_release(&channel, &config).await?, &config)?,
+        };
+        print_plan(&plan);
+        return Ok(());
+    }
+
+    let _lock = InstallLock::acquire()?;
+    let outcome = match file {
+        Some(file) => install_file(&file, verify, &config).await?,
+        None => update_neovim(&choose_release(&channel, &config).await?, &config, None).await?,
+    };
+    print_outcome(&outcome);
+    Ok(())
+}
+
+fn rollback(dry_run: bool) -> Result<()> {
+    let store = VersionStore::new();
+    if dry_run {
+        print_plan(&store.plan_rollback()?);
+        return Ok(());
+    }
+
+    let _lock = InstallLock::acquire()?;
+    let previous = store.current()?;
+    let result = store.rollback();
+    let (old, new) = match &result {
+        Ok((from, to)) => (Some(from.clone()), Some(to.clone())),
+        Err(_) => (previous, None),
+    };
+    history::record_result(HistoryAction::Rollback, old, new, &result);
+    let (from, to) = result?;
+    println!("Rolled back from Neovim {} to {}", from, to);
+    Ok(())
+}
+
+fn print_plan(plan: &Plan) {
+    println!("{}:", plan.title);
+    for line in plan.describe() {
+        println!("  {}", line);
+    }
+    if let Some(elevator) = plan.elevator() {
+        println!("Steps marked [{}] run as root", elevator);
+    }
+}
+
+fn print_outcome(outcome: &InstallOutcome) {
+    println!(
+        "Neovim {} has been installed to {}",
+        outcome.version,
+        outcome.target.display()
+    );
+    if let Some(replaced) = &outcome.replaced {
+        println!("Replaced Neovim {}", replaced);
+    }
+    for warning in &outcome.warnings {
+        println!("Warning: {}", warning);
+    }
+}
+
+fn versions(command: VersionsCommand) -> Result<()> {
+    let store = VersionStore::new();
+    match command {
+        VersionsCommand::List => {
+            let versions = store.list()?;
+            if versions.is_empty() {
+                println!("No Neovim versions installed by neviraller");
+            }
+            for installed in versions {
+                let marker = if installed.current { "*" } else { " " };
+                println!(
+                    "{} {:<32} {}",
+                    marker,
+                    installed.version.to_string(),
+                    installed.path.display()
+                );
+            }
+        }
+        VersionsCommand::Use { version } => {
+            let _lock = InstallLock::acquire()?;
+            let previous = store.current()?;
+            let result = store.switch(&version);
+            history::record_result(
+                HistoryAction::Switch,
+                previous,
+                Some(version.clone()),
+                &result,
+            );
+            match result? {
+                Some(replaced) => println!("Switched from Neovim {} to {}", replaced, version),
+                None => println!("Switched to Neovim {}", version),
+            }
+        }
+        VersionsCommand::Remove { version } => {
+            let _lock = InstallLock::acquire()?;
+            let result = store.remove(&version);
+            history::record_result(HistoryAction::Remove, Some(version.clone()), None, &result);
+            result?;
+            println!("Removed Neovim {}", version);
+        }
+    }
+
+    Ok(())
+}
@@ -1,9 +1,14 @@
-use color_eyre::eyre::Result;
+use clap::Parser;
+use color_eyre::eyre::{eyre, Result};
 
+mod cli;
 mod neovim_nightly;
 mod tui;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tui::run_term().await
+    match cli::Cli::parse().command {
+        Some(command) => cli::run(command).await.map_err(|e| eyre!(e)),
+        None => tui::run_term().await,
+    }
 }
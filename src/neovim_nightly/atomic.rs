@@ -0,0 +1,139 @@
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{elevate::Elevator, version::NvimVersion, Result};
+
+/// Runs `path --version` and parses the reported version, failing when the
+/// binary can't be started or exits unsuccessfully.
+pub fn validate_binary(path: &Path) -> Result<NvimVersion> {
+    let output = Command::new(path)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", path.display(), e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} --version failed ({}): {}",
+            path.display(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    let first_line = std::str::from_utf8(&output.stdout)?
+        .lines()
+        .next()
+        .ok_or_else(|| format!("{} --version printed nothing", path.display()))?;
+
+    Ok(first_line.parse()?)
+}
+
+/// Removes files and directories left behind by work that is dropped before it
+/// finishes, such as a cancelled install. Work that runs to its end, whether
+/// it succeeds or not, `disarm`s it and cleans up after itself.
+pub struct RemoveOnDrop {
+    paths: Vec<PathBuf>,
+}
+
+impl RemoveOnDrop {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            paths: paths.into_iter().collect(),
+        }
+    }
+
+    pub fn disarm(mut self) {
+        self.paths.clear();
+    }
+}
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_dir_all(path).or_else(|_| std::fs::remove_file(path));
+        }
+    }
+}
+
+/// Hidden sibling of `target` used to prepare a replacement in the same
+/// directory, so the final rename stays on one filesystem.
+pub fn staged_path(target: &Path) -> PathBuf {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    target.with_file_name(format!(".{}.new-{}", name, std::process::id()))
+}
+
+/// Copies `source` next to `target`, makes it executable, validates it and
+/// renames it over `target`. On any failure `target` is left untouched.
+pub fn replace_file(source: &Path, target: &Path) -> Result<NvimVersion> {
+    let staged = staged_path(target);
+    let result = (|| {
+        std::fs::copy(source, &staged)?;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+        let version = validate_binary(&staged)?;
+        std::fs::rename(&staged, target)?;
+        Ok(version)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&staged);
+    }
+    result
+}
+
+/// Replaces `target` with a symlink to `link_target` in one rename, after
+/// checking that the new link starts Neovim. The filesystem changes go through
+/// `elevator` when it elevates.
+pub fn replace_symlink(link_target: &Path, target: &Path, elevator: Elevator) -> Result<()> {
+    let staged = staged_path(target);
+    let elevated = elevator.is_elevated();
+    let result = (|| {
+        if elevated {
+            elevator.run(
+                elevator
+                    .command("ln")
+                    .arg("-s")
+                    .arg(link_target)
+                    .arg(&staged),
+            )?;
+        } else {
+            symlink(link_target, &staged)?;
+        }
+
+        validate_binary(&staged)?;
+
+        if elevated {
+            elevator.run(
+                elevator
+                    .command("mv")
+                    .arg("-f")
+                    .arg("-T")
+                    .arg(&staged)
+                    .arg(target),
+            )
+        } else {
+            Ok(std::fs::rename(&staged, target)?)
+        }
+    })();
+
+    if result.is_err() {
+        if elevated {
+            let _ = elevator.run(elevator.command("rm").arg("-f").arg(&staged));
+        } else {
+            let _ = std::fs::remove_file(&staged);
+        }
+    }
+    result
+}
+
+pub fn run(command: &mut Command) -> Result<()> {
+    let status = command
+        .status()
+        .map_err(|e| format!("Failed to run {:?}: {}", command.get_program(), e))?;
+
+    if !status.success() {
+        return Err(format!("{:?} failed: {}", command, status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,470 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+    channel::Channel,
+    config::Config,
+    download::Progress,
+    paths,
+    platform::Platform,
+    release::Release,
+    store,
+    update::{choose_release, download_artifact, install_file, FileChecksum, InstallOutcome},
+    version::NvimVersion,
+    Result,
+};
+
+/// Top-level directory inside a bundle archive.
+const BUNDLE_ROOT: &str = "neviraller-bundle";
+const MANIFEST_FILE: &str = "bundle.json";
+const RELEASE_FILE: &str = "release.json";
+const ARTIFACT_DIR: &str = "artifact";
+const CONFIG_DIR: &str = "config";
+const PLUGINS_DIR: &str = "plugins";
+/// Where lazy.nvim keeps plugin checkouts, relative to Neovim's data dir.
+const PLUGINS_SUBDIR: &str = "lazy";
+
+/// Describes what a bundle holds; stored as `bundle.json` in the archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: NvimVersion,
+    pub tag: String,
+    pub platform: String,
+    pub artifact: String,
+    pub sha256: String,
+    pub created_at: String,
+    pub config: bool,
+    pub plugins: bool,
+}
+
+pub struct ImportOutcome {
+    pub manifest: BundleManifest,
+    pub install: InstallOutcome,
+    /// Existing directories moved aside to make room for the bundled ones.
+    pub backups: Vec<PathBuf>,
+}
+
+/// Downloads the release for `channel` and packs it, together with its
+/// checksum, the release metadata, the Neovim config and the plugin sources,
+/// into a `.tar.gz` at `dest` that `import_bundle` can install offline.
+pub async fn export_bundle(
+    channel: &Channel,
+    config: &Config,
+    dest: &Path,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<BundleManifest> {
+    let choice = choose_release(channel, config).await?;
+    let artifact = download_artifact(&choice, progress).await?;
+    let artifact_name = artifact
+        .path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("Downloaded artifact has no file name")?
+        .to_string();
+
+    let config_dir = paths::nvim_config_dir();
+    let plugins_dir = paths::nvim_data_dir().join(PLUGINS_SUBDIR);
+    let manifest = BundleManifest {
+        version: artifact.version.clone(),
+        tag: artifact.release.tag_name.clone(),
+        platform: artifact.platform.to_string(),
+        artifact: artifact_name,
+        sha256: artifact.sha256.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        config: config_dir.is_dir(),
+        plugins: plugins_dir.is_dir(),
+    };
+
+    let (bundled, release) = (manifest.clone(), artifact.release.clone());
+    let (artifact_path, archive_path) = (artifact.path.clone(), dest.to_path_buf());
+    let written = tokio::task::spawn_blocking(move || {
+        write_bundle(
+            &archive_path,
+            &bundled,
+            &release,
+            &artifact_path,
+            &config_dir,
+            &plugins_dir,
+        )
+    })
+    .await?;
+    let _ = tokio::fs::remove_file(&artifact.path).await;
+    written?;
+
+    Ok(manifest)
+}
+
+/// Installs Neovim from a bundle made by `export_bundle` without touching the
+/// network. With `restore_config`, the bundled config and plugins replace the
+/// current ones, which are moved aside first. A bundle made for another
+/// platform is refused unless `force` is set.
+pub async fn import_bundle(
+    bundle: &Path,
+    config: &Config,
+    restore_config: bool,
+    force: bool,
+) -> Result<ImportOutcome> {
+    let unpacked = paths::cache_dir()
+        .join("bundles")
+        .join(std::process::id().to_string());
+    let (archive, dest) = (bundle.to_path_buf(), unpacked.clone());
+    tokio::task::spawn_blocking(move || unpack_bundle(&archive, &dest)).await??;
+
+    let result = import_unpacked(&unpacked, config, restore_config, force).await;
+    let _ = tokio::fs::remove_dir_all(&unpacked).await;
+    result
+}
+
+/// Writes the `.tar.gz` `export_bundle` produces to `dest`, with the config
+/// and plugins only when `manifest` says they are bundled. Nothing is left at
+/// `dest` when it fails.
+fn write_bundle(
+    dest: &Path,
+    manifest: &BundleManifest,
+    release: &Release,
+    artifact: &Path,
+    config_dir: &Path,
+    plugins_dir: &Path,
+) -> Result<()> {
+    let written = (|| -> Result<()> {
+        let root = Path::new(BUNDLE_ROOT);
+        let mut archive =
+            tar::Builder::new(GzEncoder::new(File::create(dest)?, Compression::default()));
+        archive.follow_symlinks(false);
+
+        append_bytes(
+            &mut archive,
+            &root.join(MANIFEST_FILE),
+            &serde_json::to_vec_pretty(manifest)?,
+        )?;
+        append_bytes(
+            &mut archive,
+            &root.join(RELEASE_FILE),
+            &serde_json::to_vec_pretty(release)?,
+        )?;
+        let artifact_dir = root.join(ARTIFACT_DIR);
+        archive.append_path_with_name(artifact, artifact_dir.join(&manifest.artifact))?;
+        append_bytes(
+            &mut archive,
+            &artifact_dir.join(format!("{}.sha256sum", manifest.artifact)),
+            format!("{}  {}\n", manifest.sha256, manifest.artifact).as_bytes(),
+        )?;
+        if manifest.config {
+            append_tree(&mut archive, &root.join(CONFIG_DIR), config_dir)?;
+        }
+        if manifest.plugins {
+            append_tree(&mut archive, &root.join(PLUGINS_DIR), plugins_dir)?;
+        }
+
+        archive.into_inner()?.finish()?;
+        Ok(())
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    written
+}
+
+/// Unpacks a bundle into the empty directory `dest`.
+fn unpack_bundle(bundle: &Path, dest: &Path) -> Result<()> {
+    let _ = std::fs::remove_dir_all(dest);
+    std::fs::create_dir_all(dest)?;
+    store::extract_tarball(bundle, dest)?;
+    Ok(())
+}
+
+/// Adds `dir` to `archive` as `name`. Symlinks that stay inside the bundle are
+/// kept; ones pointing out of it, such as a config managed with stow, are
+/// replaced by what they point to, since `import_bundle` refuses to unpack
+/// them.
+fn append_tree(archive: &mut tar::Builder<GzEncoder<File>>, name: &Path, dir: &Path) -> Result<()> {
+    append_dir(archive, name, &std::fs::canonicalize(dir)?, &mut Vec::new())
+}
+
+/// `append_tree` for the resolved directory `dir`; `visiting` holds the
+/// directories it is in, to catch symlinks that loop back.
+fn append_dir(
+    archive: &mut tar::Builder<GzEncoder<File>>,
+    name: &Path,
+    dir: &Path,
+    visiting: &mut Vec<PathBuf>,
+) -> Result<()> {
+    if visiting.iter().any(|ancestor| ancestor == dir) {
+        return Err(format!(
+            "Can't bundle {}, it links back to {}",
+            name.display(),
+            dir.display()
+        )
+        .into());
+    }
+    visiting.push(dir.to_path_buf());
+    archive.append_dir(name, dir)?;
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let (path, entry_name) = (entry.path(), name.join(entry.file_name()));
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            append_dir(archive, &entry_name, &path, visiting)?;
+            continue;
+        }
+        if !file_type.is_symlink() {
+            archive.append_path_with_name(&path, &entry_name)?;
+            continue;
+        }
+
+        // Unpacking drops the bundle's top-level directory.
+        let relative: PathBuf = entry_name.components().skip(1).collect();
+        if store::link_stays_inside(&relative, &std::fs::read_link(&path)?) {
+            archive.append_path_with_name(&path, &entry_name)?;
+            continue;
+        }
+        let target = std::fs::canonicalize(&path)
+            .map_err(|e| format!("Can't bundle the broken symlink {}: {}", path.display(), e))?;
+        if target.is_dir() {
+            append_dir(archive, &entry_name, &target, visiting)?;
+        } else {
+            archive.append_path_with_name(&target, &entry_name)?;
+        }
+    }
+
+    visiting.pop();
+    Ok(())
+}
+
+async fn import_unpacked(
+    unpacked: &Path,
+    config: &Config,
+    restore_config: bool,
+    force: bool,
+) -> Result<ImportOutcome> {
+    let manifest_json = tokio::fs::read(unpacked.join(MANIFEST_FILE))
+        .await
+        .map_err(|e| {
+            format!(
+                "Not a neviraller bundle, {} is missing: {}",
+                MANIFEST_FILE, e
+            )
+        })?;
+    let manifest: BundleManifest = serde_json::from_slice(&manifest_json)?;
+
+    let platform = Platform::detect().to_string();
+    let foreign = manifest.platform != platform;
+    if foreign && !force {
+        return Err(format!(
+            "The bundle was made for {}, this machine is {}; import it anyway with --force",
+            manifest.platform, platform
+        )
+        .into());
+    }
+
+    // The sidecar written on export makes `install_file` verify the artifact.
+    let artifact = unpacked.join(ARTIFACT_DIR).join(&manifest.artifact);
+    let mut install = install_file(&artifact, &FileChecksum::Sidecar, config).await?;
+    if foreign {
+        install.warnings.push(format!(
+            "The bundle was made for {}, this machine is {}",
+            manifest.platform, platform
+        ));
+    }
+
+    let mut backups = Vec::new();
+    if restore_config {
+        let restores = [
+            (manifest.config, CONFIG_DIR, paths::nvim_config_dir()),
+            (
+                manifest.plugins,
+                PLUGINS_DIR,
+                paths::nvim_data_dir().join(PLUGINS_SUBDIR),
+            ),
+        ];
+        for (bundled, name, target) in restores {
+            if bundled {
+                backups.extend(restore_dir(&unpacked.join(name), &target)?);
+            }
+        }
+    }
+
+    Ok(ImportOutcome {
+        manifest,
+        install,
+        backups,
+    })
+}
+
+/// Moves `source` to `target`, first renaming an existing `target` to a
+/// timestamped backup, which is returned.
+fn restore_dir(source: &Path, target: &Path) -> Result<Option<PathBuf>> {
+    let backup = if target.exists() {
+        let mut name = target.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(
+            ".neviraller-backup-{}",
+            chrono::Local::now().format("%Y%m%d%H%M%S")
+        ));
+        let backup = target.with_file_name(name);
+        std::fs::rename(target, &backup)?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(source, target).is_err() {
+        // The cache and the target may be on different filesystems.
+        copy_dir(source, target)?;
+    }
+
+    Ok(backup)
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let dest = target.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else if file_type.is_symlink() {
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, &dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+fn append_bytes(
+    archive: &mut tar::Builder<GzEncoder<File>>,
+    path: &Path,
+    contents: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp() as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "neviraller-bundle-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn manifest(config: bool) -> BundleManifest {
+        BundleManifest {
+            version: "v0.10.2".parse().unwrap(),
+            tag: "v0.10.2".to_string(),
+            platform: Platform::detect().to_string(),
+            artifact: "nvim-linux-x86_64.tar.gz".to_string(),
+            sha256: "00".repeat(32),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            config,
+            plugins: false,
+        }
+    }
+
+    fn release() -> Release {
+        Release {
+            tag_name: "v0.10.2".to_string(),
+            body: None,
+            assets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trips_a_config_with_symlinks_out_of_it() {
+        let dir = temp_dir("symlinks");
+        let (config, dotfiles) = (dir.join("config"), dir.join("dotfiles"));
+        std::fs::create_dir_all(config.join("lua")).unwrap();
+        std::fs::create_dir_all(dotfiles.join("after")).unwrap();
+        std::fs::write(config.join("init.lua"), "require('plugins')").unwrap();
+        std::fs::write(dotfiles.join("options.lua"), "vim.o.number = true").unwrap();
+        std::fs::write(dotfiles.join("after").join("ftplugin.lua"), "ft").unwrap();
+        symlink("../init.lua", config.join("lua").join("init.lua")).unwrap();
+        symlink(
+            dotfiles.join("options.lua"),
+            config.join("lua").join("options.lua"),
+        )
+        .unwrap();
+        symlink(dotfiles.join("after"), config.join("after")).unwrap();
+        let artifact = dir.join("nvim-linux-x86_64.tar.gz");
+        std::fs::write(&artifact, "artifact").unwrap();
+
+        let bundle = dir.join("bundle.tar.gz");
+        let exported = manifest(true);
+        write_bundle(
+            &bundle,
+            &exported,
+            &release(),
+            &artifact,
+            &config,
+            &dir.join("plugins"),
+        )
+        .unwrap();
+        let unpacked = dir.join("unpacked");
+        unpack_bundle(&bundle, &unpacked).unwrap();
+
+        let imported: BundleManifest =
+            serde_json::from_slice(&std::fs::read(unpacked.join(MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(imported.version, exported.version);
+        let sidecar = unpacked
+            .join(ARTIFACT_DIR)
+            .join("nvim-linux-x86_64.tar.gz.sha256sum");
+        assert!(std::fs::read_to_string(sidecar)
+            .unwrap()
+            .starts_with(&exported.sha256));
+
+        let restored = unpacked.join(CONFIG_DIR);
+        let read = |path: &str| std::fs::read_to_string(restored.join(path)).unwrap();
+        assert_eq!(read("lua/init.lua"), "require('plugins')");
+        assert!(restored.join("lua/init.lua").is_symlink());
+        assert_eq!(read("lua/options.lua"), "vim.o.number = true");
+        assert!(!restored.join("lua/options.lua").is_symlink());
+        assert_eq!(read("after/ftplugin.lua"), "ft");
+    }
+
+    #[test]
+    fn refuses_to_export_a_broken_symlink() {
+        let dir = temp_dir("broken");
+        let config = dir.join("config");
+        std::fs::create_dir_all(&config).unwrap();
+        symlink(dir.join("missing.lua"), config.join("init.lua")).unwrap();
+        let artifact = dir.join("nvim-linux-x86_64.tar.gz");
+        std::fs::write(&artifact, "artifact").unwrap();
+
+        let bundle = dir.join("bundle.tar.gz");
+        let error = write_bundle(
+            &bundle,
+            &manifest(true),
+            &release(),
+            &artifact,
+            &config,
+            &dir,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("broken symlink"), "{}", error);
+        assert!(!bundle.exists());
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Channel {
+    #[default]
+    Nightly,
+    Stable,
+    Tag(String),
+}
+
+impl Channel {
+    pub fn tag(&self) -> &str {
+        match self {
+            Channel::Nightly => "nightly",
+            Channel::Stable => "stable",
+            Channel::Tag(tag) => tag,
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Channel::Nightly => write!(f, "Nightly"),
+            Channel::Stable => write!(f, "Stable"),
+            Channel::Tag(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "nightly" => Ok(Channel::Nightly),
+            "stable" => Ok(Channel::Stable),
+            tag if tag.starts_with('v') && tag.len() > 1 => Ok(Channel::Tag(tag.to_string())),
+            _ => Err(format!("Unknown channel: {}", s)),
+        }
+    }
+}
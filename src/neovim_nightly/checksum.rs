@@ -0,0 +1,194 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::{
+    release::{Asset, Release},
+    Result,
+};
+
+#[derive(Debug)]
+pub struct ChecksumMismatch {
+    pub file: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Checksum mismatch for {}: expected sha256 {}, got {}",
+            self.file, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Published sha256 of `asset`: the digest GitHub reports for the asset, or the
+/// contents of the accompanying `<asset>.sha256sum` file.
+pub async fn expected_sha256(release: &Release, asset: &Asset) -> Result<String> {
+    if let Some(digest) = asset
+        .digest
+        .as_deref()
+        .and_then(|d| d.strip_prefix("sha256:"))
+    {
+        return Ok(digest.to_lowercase());
+    }
+
+    let sum_asset = release
+        .asset(&format!("{}.sha256sum", asset.name))
+        .ok_or_else(|| format!("No published checksum for {}", asset.name))?;
+    let sum_file = reqwest::get(&sum_asset.browser_download_url)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    parse_sha256sum(&sum_file, &asset.name)
+        .ok_or_else(|| format!("Malformed checksum file {}", sum_asset.name).into())
+}
+
+/// Checksum published next to a local artifact, either as `<file>.sha256sum`
+/// or in a `shasum.txt` listing every artifact of a release. `None` when
+/// neither exists.
+pub async fn local_sha256(artifact: &Path) -> Result<Option<String>> {
+    let (file_name, sum_paths) = local_sum_paths(artifact)?;
+    for sum_path in sum_paths {
+        let contents = match tokio::fs::read_to_string(&sum_path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        return parse_sha256sum(&contents, file_name)
+            .map(Some)
+            .ok_or_else(|| format!("Malformed checksum file {}", sum_path.display()).into());
+    }
+
+    Ok(None)
+}
+
+/// Whether `local_sha256` finds a checksum file for `artifact`.
+pub fn has_local_sha256(artifact: &Path) -> bool {
+    local_sum_paths(artifact).is_ok_and(|(_, sum_paths)| sum_paths.iter().any(|path| path.exists()))
+}
+
+fn local_sum_paths(artifact: &Path) -> Result<(&str, [PathBuf; 2])> {
+    let file_name = artifact
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid artifact path {}", artifact.display()))?;
+    let sidecar = artifact.with_file_name(format!("{}.sha256sum", file_name));
+    let listing = artifact.with_file_name("shasum.txt");
+    Ok((file_name, [sidecar, listing]))
+}
+
+/// Parses `sha256sum` output, picking the line naming exactly `file_name` when
+/// the file lists several artifacts.
+pub fn parse_sha256sum(contents: &str, file_name: &str) -> Option<String> {
+    let entries: Vec<(&str, Option<&str>)> = contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let hash = parts.next()?;
+            let name = parts.next().map(|name| name.trim_start_matches('*'));
+            Some((hash, name))
+        })
+        .collect();
+
+    let (hash, _) = match entries.as_slice() {
+        [single] => *single,
+        _ => *entries.iter().find(|(_, name)| *name == Some(file_name))?,
+    };
+
+    parse_sha256(hash)
+}
+
+/// `hash` in lowercase if it is a hex-encoded SHA-256.
+pub fn parse_sha256(hash: &str) -> Option<String> {
+    let hash = hash.trim();
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_lowercase())
+}
+
+pub async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+pub async fn verify_file(path: &Path, expected: &str) -> Result<()> {
+    let actual = sha256_file(path).await?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(Box::new(ChecksumMismatch {
+            file: path.display().to_string(),
+            expected: expected.to_string(),
+            actual,
+        }));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+    const OTHER: &str = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
+
+    #[test]
+    fn parses_a_sha256sum_file() {
+        let cases = [
+            format!("{}  nvim-linux64.tar.gz\n", HASH),
+            format!("{} *nvim-linux64.tar.gz\n", HASH),
+            format!("{}\n", HASH.to_uppercase()),
+        ];
+        for contents in cases {
+            assert_eq!(
+                parse_sha256sum(&contents, "nvim-linux64.tar.gz").as_deref(),
+                Some(HASH),
+                "{}",
+                contents
+            );
+        }
+        assert_eq!(
+            parse_sha256sum("not a hash  nvim.tar.gz", "nvim.tar.gz"),
+            None
+        );
+    }
+
+    #[test]
+    fn picks_the_exact_file_from_a_shasum_listing() {
+        let listing = format!(
+            "{other}  foo-nvim-linux64.tar.gz\n\
+             {other}  old/nvim-linux64.tar.gz\n\
+             {hash}  nvim-linux64.tar.gz\n\
+             {other}  nvim-linux64.tar.gz.zsync\n",
+            hash = HASH,
+            other = OTHER
+        );
+        assert_eq!(
+            parse_sha256sum(&listing, "nvim-linux64.tar.gz").as_deref(),
+            Some(HASH)
+        );
+
+        let without = format!(
+            "{other}  foo-nvim-linux64.tar.gz\n{other}  old/nvim-linux64.tar.gz\n",
+            other = OTHER
+        );
+        assert_eq!(parse_sha256sum(&without, "nvim-linux64.tar.gz"), None);
+    }
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::{paths, platform, Result};
+
+const SYSTEM_PREFIX: &str = "/usr/local";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallFormat {
+    /// The AppImage when FUSE is available, the tarball otherwise.
+    #[default]
+    Auto,
+    AppImage,
+    Tarball,
+}
+
+impl InstallFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstallFormat::Auto => "auto",
+            InstallFormat::AppImage => "AppImage",
+            InstallFormat::Tarball => "tarball",
+        }
+    }
+}
+
+/// Tool used to run commands as root when installing into a prefix the user
+/// can't write to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElevatorKind {
+    /// The first of sudo, doas and pkexec that is installed.
+    #[default]
+    Auto,
+    Sudo,
+    Doas,
+    Pkexec,
+}
+
+/// CMake build type used when building Neovim from source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum BuildType {
+    Release,
+    #[default]
+    RelWithDebInfo,
+    Debug,
+}
+
+impl BuildType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuildType::Release => "Release",
+            BuildType::RelWithDebInfo => "RelWithDebInfo",
+            BuildType::Debug => "Debug",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BuildConfig {
+    pub build_type: BuildType,
+    /// Branch, tag or commit to build instead of the selected channel's tag.
+    pub git_ref: Option<String>,
+    /// Extra CMake flags, passed through the Makefile's `CMAKE_EXTRA_FLAGS`.
+    pub cmake_flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Install prefix; Neovim goes into `<prefix>/bin`. Defaults to `~/.local`
+    /// when it is writable and `/usr/local` otherwise.
+    pub prefix: Option<PathBuf>,
+    pub format: InstallFormat,
+    /// Unpack the AppImage with `--appimage-extract` instead of running it
+    /// through FUSE.
+    pub appimage_extract: bool,
+    pub elevator: ElevatorKind,
+    pub build: BuildConfig,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        paths::config_dir().join("config.json")
+    }
+
+    pub fn load() -> Result<Config> {
+        match std::fs::read(Self::path()) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn install_format(&self) -> InstallFormat {
+        match self.format {
+            InstallFormat::Auto if platform::fuse_available() => InstallFormat::AppImage,
+            InstallFormat::Auto => InstallFormat::Tarball,
+            format => format,
+        }
+    }
+
+    pub fn install_prefix(&self) -> PathBuf {
+        if let Some(prefix) = &self.prefix {
+            return prefix.clone();
+        }
+
+        let local = paths::home_dir().join(".local");
+        if paths::is_writable(&local) {
+            local
+        } else {
+            PathBuf::from(SYSTEM_PREFIX)
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::mpsc::UnboundedSender};
+
+use super::Result;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_ATTEMPTS: u32 = 5;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    /// Average transfer rate of the current attempt in bytes per second.
+    pub speed: f64,
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    fn new(downloaded: u64, resumed_from: u64, total: Option<u64>, elapsed: Duration) -> Self {
+        let seconds = elapsed.as_secs_f64();
+        let speed = if seconds > 0.0 {
+            downloaded.saturating_sub(resumed_from) as f64 / seconds
+        } else {
+            0.0
+        };
+        let eta = total
+            .filter(|_| speed > 0.0)
+            .map(|total| Duration::from_secs_f64(total.saturating_sub(downloaded) as f64 / speed));
+
+        Progress {
+            downloaded,
+            total,
+            speed,
+            eta,
+        }
+    }
+
+    pub fn ratio(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Sidecar stored next to a `.part` file so a later attempt can check that the
+/// partial data still belongs to the same remote file before resuming.
+#[derive(Debug, Serialize, Deserialize)]
+struct PartialMeta {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    total: Option<u64>,
+}
+
+impl PartialMeta {
+    fn validator(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+enum Failure {
+    /// The transfer broke off and may be resumed by another attempt.
+    Interrupted(Box<dyn std::error::Error + Send + Sync>),
+    Fatal(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl From<reqwest::Error> for Failure {
+    fn from(e: reqwest::Error) -> Self {
+        Failure::Interrupted(e.into())
+    }
+}
+
+impl From<std::io::Error> for Failure {
+    fn from(e: std::io::Error) -> Self {
+        Failure::Fatal(e.into())
+    }
+}
+
+impl From<serde_json::Error> for Failure {
+    fn from(e: serde_json::Error) -> Self {
+        Failure::Fatal(e.into())
+    }
+}
+
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+fn meta_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part.json");
+    dest.with_file_name(name)
+}
+
+/// Downloads `url` into `dest`, keeping partial data in `<dest>.part` with a
+/// `<dest>.part.json` sidecar. Interrupted transfers are retried and resumed
+/// with `Range`/`If-Range`; a server that ignores the range gets a full
+/// download instead.
+pub async fn download(
+    url: &str,
+    dest: &Path,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build the HTTP client: {}", e))?;
+    let part = part_path(dest);
+    let meta = meta_path(dest);
+
+    // A cancelled or failed download keeps its partial data for the next one
+    // to resume; `If-Range` makes sure it still matches the remote file.
+    fetch_with_retries(&client, url, &part, &meta, progress).await?;
+
+    tokio::fs::rename(&part, dest).await?;
+    let _ = tokio::fs::remove_file(&meta).await;
+
+    Ok(())
+}
+
+async fn fetch_with_retries(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    meta: &Path,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match fetch(client, url, part, meta, progress).await {
+            Ok(()) => return Ok(()),
+            Err(Failure::Fatal(e)) => return Err(e),
+            Err(Failure::Interrupted(e)) if attempt >= MAX_ATTEMPTS => {
+                return Err(format!("Download failed after {} attempts: {}", attempt, e).into())
+            }
+            Err(Failure::Interrupted(_)) => tokio::time::sleep(RETRY_DELAY * attempt).await,
+        }
+    }
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    meta_path: &Path,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> std::result::Result<(), Failure> {
+    let saved = read_meta(meta_path).await.filter(|meta| meta.url == url);
+    let offset = match tokio::fs::metadata(part).await {
+        Ok(metadata) if saved.is_some() => metadata.len(),
+        _ => 0,
+    };
+
+    let mut request = client.get(url);
+    if let Some(validator) = saved.as_ref().and_then(PartialMeta::validator) {
+        if offset > 0 {
+            request = request
+                .header(header::RANGE, format!("bytes={}-", offset))
+                .header(header::IF_RANGE, validator);
+        }
+    }
+
+    // The stall timeout also covers waiting for the response headers.
+    let response = match tokio::time::timeout(STALL_TIMEOUT, request.send()).await {
+        Ok(response) => response?,
+        Err(_) => {
+            return Err(Failure::Interrupted(
+                format!("No response from {} for {}s", url, STALL_TIMEOUT.as_secs()).into(),
+            ))
+        }
+    };
+    let status = response.status();
+
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // Either the partial file is already complete or it no longer matches
+        // the remote one; in the latter case start over on the next attempt.
+        if saved.and_then(|meta| meta.total) == Some(offset) {
+            return Ok(());
+        }
+        let _ = tokio::fs::remove_file(part).await;
+        return Err(Failure::Interrupted(
+            format!("Server rejected resuming {}", url).into(),
+        ));
+    }
+    if status.is_server_error() {
+        return Err(Failure::Interrupted(
+            format!("Failed to download {}: {}", url, status).into(),
+        ));
+    }
+    if !status.is_success() {
+        return Err(Failure::Fatal(
+            format!("Failed to download {}: {}", url, status).into(),
+        ));
+    }
+
+    let resumed = status == StatusCode::PARTIAL_CONTENT;
+    let resumed_from = if resumed { offset } else { 0 };
+    let total = if resumed {
+        content_range_total(&response).or(saved.as_ref().and_then(|meta| meta.total))
+    } else {
+        response.content_length()
+    };
+
+    if !resumed {
+        let header_value = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value: &header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let meta = PartialMeta {
+            url: url.to_string(),
+            etag: header_value(header::ETAG),
+            last_modified: header_value(header::LAST_MODIFIED),
+            total,
+        };
+        tokio::fs::write(meta_path, serde_json::to_vec(&meta)?).await?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(part)
+        .await?;
+    let mut stream = response.bytes_stream();
+
+    let started = Instant::now();
+    let mut last_report = started;
+    let mut downloaded = resumed_from;
+
+    loop {
+        let chunk = match tokio::time::timeout(STALL_TIMEOUT, stream.next()).await {
+            Ok(Some(Ok(chunk))) => chunk,
+            Ok(None) => break,
+            Ok(Some(Err(e))) => {
+                file.flush().await?;
+                return Err(e.into());
+            }
+            Err(_) => {
+                file.flush().await?;
+                return Err(Failure::Interrupted(
+                    format!("No data received for {}s", STALL_TIMEOUT.as_secs()).into(),
+                ));
+            }
+        };
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+
+        if let Some(progress) = progress {
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                last_report = Instant::now();
+                let _ = progress.send(Progress::new(
+                    downloaded,
+                    resumed_from,
+                    total,
+                    started.elapsed(),
+                ));
+            }
+        }
+    }
+    file.flush().await?;
+
+    if let Some(progress) = progress {
+        let _ = progress.send(Progress::new(
+            downloaded,
+            resumed_from,
+            total,
+            started.elapsed(),
+        ));
+    }
+
+    match total {
+        Some(total) if downloaded < total => Err(Failure::Interrupted(
+            format!("Connection closed after {} of {} bytes", downloaded, total).into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn read_meta(path: &Path) -> Option<PartialMeta> {
+    let contents = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::neovim_nightly::test_server::{self, response, response_with_length};
+
+    const BODY: &[u8] = b"0123456789";
+
+    fn temp_dest(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "neviraller-download-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("nvim.tar.gz")
+    }
+
+    /// Serves `respond` and records the lowercased head of every request.
+    async fn serve_logged<F>(respond: F) -> (String, Arc<Mutex<Vec<String>>>)
+    where
+        F: Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+    {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let server = test_server::serve(move |request| {
+            let request = request.to_lowercase();
+            log.lock().unwrap().push(request.clone());
+            respond(&request)
+        })
+        .await;
+        (format!("{}/nvim.tar.gz", server), requests)
+    }
+
+    /// The full body, cut off after the first four bytes.
+    fn dropped_mid_transfer() -> Vec<u8> {
+        let etag = ("ETag", "\"abc\"".to_string());
+        response_with_length("200 OK", &[etag], &BODY[..4], BODY.len())
+    }
+
+    fn assert_finished(dest: &Path) {
+        assert_eq!(std::fs::read(dest).unwrap(), BODY);
+        assert!(!part_path(dest).exists());
+        assert!(!meta_path(dest).exists());
+    }
+
+    #[tokio::test]
+    async fn resumes_with_partial_content() {
+        let (url, requests) = serve_logged(|request| {
+            if request.contains("range: bytes=4-") && request.contains("if-range: \"abc\"") {
+                let range = ("Content-Range", "bytes 4-9/10".to_string());
+                response("206 Partial Content", &[range], &BODY[4..])
+            } else {
+                dropped_mid_transfer()
+            }
+        })
+        .await;
+        let dest = temp_dest("resume");
+
+        download(&url, &dest, None).await.unwrap();
+
+        assert_finished(&dest);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn starts_over_when_the_range_is_ignored() {
+        let (url, requests) = serve_logged(|request| {
+            if request.contains("range:") {
+                response("200 OK", &[], BODY)
+            } else {
+                dropped_mid_transfer()
+            }
+        })
+        .await;
+        let dest = temp_dest("ignored-range");
+
+        download(&url, &dest, None).await.unwrap();
+
+        assert_finished(&dest);
+        assert!(requests.lock().unwrap()[1].contains("range: bytes=4-"));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_partial_download_when_cancelled() {
+        let (url, _) = serve_logged(|_| dropped_mid_transfer()).await;
+        let dest = temp_dest("cancel");
+
+        // The first attempt is cut off and the retry waits for a second, so
+        // the download is dropped with its partial data on disk.
+        let cancelled = tokio::time::timeout(RETRY_DELAY / 2, download(&url, &dest, None)).await;
+
+        assert!(cancelled.is_err());
+        assert!(part_path(&dest).exists());
+        assert!(meta_path(&dest).exists());
+        assert!(!dest.exists());
+    }
+
+    /// Leaves a partial download of `len` bytes of `BODY` behind.
+    fn write_partial(dest: &Path, url: &str, len: usize) {
+        std::fs::write(part_path(dest), &BODY[..len]).unwrap();
+        let meta = PartialMeta {
+            url: url.to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            total: Some(BODY.len() as u64),
+        };
+        std::fs::write(meta_path(dest), serde_json::to_vec(&meta).unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn finishes_a_complete_part_on_416() {
+        let (url, _) = serve_logged(|_| response("416 Range Not Satisfiable", &[], b"")).await;
+        let dest = temp_dest("complete-416");
+        write_partial(&dest, &url, BODY.len());
+
+        download(&url, &dest, None).await.unwrap();
+
+        assert_finished(&dest);
+    }
+
+    #[tokio::test]
+    async fn starts_over_on_416_for_a_stale_part() {
+        let (url, requests) = serve_logged(|request| {
+            if request.contains("range:") {
+                response("416 Range Not Satisfiable", &[], b"")
+            } else {
+                response("200 OK", &[], BODY)
+            }
+        })
+        .await;
+        let dest = temp_dest("stale-416");
+        write_partial(&dest, &url, 4);
+
+        download(&url, &dest, None).await.unwrap();
+
+        assert_finished(&dest);
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("range: bytes=4-"));
+        assert!(!requests[1].contains("range:"));
+    }
+}
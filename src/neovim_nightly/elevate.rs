@@ -0,0 +1,200 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use super::{atomic, config::ElevatorKind, paths, platform, Result};
+
+/// How commands that change a directory are run: directly when the user can
+/// write to it, otherwise through a tool that runs them as root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Elevator {
+    Direct,
+    Sudo,
+    Doas,
+    /// Asks for the password in a desktop dialog.
+    Pkexec,
+}
+
+/// Tried in this order when no tool is configured.
+const TOOLS: [Elevator; 3] = [Elevator::Sudo, Elevator::Doas, Elevator::Pkexec];
+
+/// Password passed to `sudo -S`, once `check_sudo_password` has accepted it.
+/// Set by the TUI, where sudo can't prompt on the terminal.
+static SUDO_PASSWORD: Mutex<Option<String>> = Mutex::new(None);
+
+impl Elevator {
+    /// The elevator for changing `dir`. Writable directories, or ones that can
+    /// be created, never need one; otherwise the configured tool is used, or the
+    /// first one installed when it is `auto`.
+    pub fn for_dir(dir: &Path, preference: ElevatorKind) -> Result<Elevator> {
+        if paths::is_writable(dir) {
+            return Ok(Elevator::Direct);
+        }
+        Self::detect(preference)
+            .map_err(|e| format!("{} is not writable and {}", dir.display(), e).into())
+    }
+
+    /// The configured tool, or the first one installed for `auto`.
+    pub fn detect(preference: ElevatorKind) -> Result<Elevator> {
+        let elevator = match preference {
+            ElevatorKind::Auto => {
+                return TOOLS
+                    .into_iter()
+                    .find(Elevator::is_installed)
+                    .ok_or_else(|| "none of sudo, doas or pkexec is installed".into())
+            }
+            ElevatorKind::Sudo => Elevator::Sudo,
+            ElevatorKind::Doas => Elevator::Doas,
+            ElevatorKind::Pkexec => Elevator::Pkexec,
+        };
+
+        if !elevator.is_installed() {
+            return Err(format!("{} is configured but not installed", elevator).into());
+        }
+        Ok(elevator)
+    }
+
+    fn program(&self) -> Option<&'static str> {
+        match self {
+            Elevator::Direct => None,
+            Elevator::Sudo => Some("sudo"),
+            Elevator::Doas => Some("doas"),
+            Elevator::Pkexec => Some("pkexec"),
+        }
+    }
+
+    fn is_installed(&self) -> bool {
+        self.program().is_none_or(platform::has_program)
+    }
+
+    pub fn is_elevated(&self) -> bool {
+        *self != Elevator::Direct
+    }
+
+    /// Whether running through the elevator would make sudo prompt for a
+    /// password on the terminal: no password has been set and sudo has no
+    /// cached credentials.
+    pub fn needs_password(&self) -> bool {
+        *self == Elevator::Sudo
+            && sudo_password().is_none()
+            && !Command::new("sudo")
+                .args(["-n", "-v"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+    }
+
+    /// Whether running through the elevator would read a password from the
+    /// terminal in a way `set_sudo_password` can't answer: doas without
+    /// `nopass` or a cached `persist` login, and pkexec without a desktop
+    /// session for its password dialog.
+    pub fn prompts_on_terminal(&self) -> bool {
+        match self {
+            Elevator::Direct | Elevator::Sudo => false,
+            Elevator::Doas => !Command::new("doas")
+                .args(["-n", "true"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success()),
+            Elevator::Pkexec => ["DISPLAY", "WAYLAND_DISPLAY"]
+                .iter()
+                .all(|name| std::env::var_os(name).is_none()),
+        }
+    }
+
+    /// A command running `program` through the elevator. Run it with `run`,
+    /// which passes sudo the password when one is set.
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        match self.program() {
+            Some(elevator) => {
+                let mut command = Command::new(elevator);
+                if *self == Elevator::Sudo && sudo_password().is_some() {
+                    command.args(["-S", "-p", ""]);
+                }
+                command.arg(program);
+                command
+            }
+            None => Command::new(program),
+        }
+    }
+
+    pub fn run(&self, command: &mut Command) -> Result<()> {
+        let password = match self {
+            Elevator::Sudo => sudo_password(),
+            _ => None,
+        };
+        let Some(password) = password else {
+            return atomic::run(command);
+        };
+
+        let status = run_with_input(command, &password)?.status;
+        if !status.success() {
+            return Err(format!("{:?} failed: {}", command, status).into());
+        }
+        Ok(())
+    }
+}
+
+fn sudo_password() -> Option<String> {
+    SUDO_PASSWORD
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Checks `password` with `sudo -v`.
+pub fn check_sudo_password(password: &str) -> Result<()> {
+    let output = run_with_input(Command::new("sudo").args(["-S", "-v", "-p", ""]), password)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("incorrect password") || stderr.contains("try again") {
+            return Err("Incorrect sudo password".into());
+        }
+        return Err(format!("sudo -v failed: {}", stderr.trim()).into());
+    }
+    Ok(())
+}
+
+/// Passes `password`, checked with `check_sudo_password`, to every later sudo
+/// command instead of letting sudo prompt for it.
+pub fn set_sudo_password(password: String) {
+    *SUDO_PASSWORD
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(password);
+}
+
+/// Forgets the password given to `set_sudo_password`, so later sudo commands
+/// can't reuse it.
+pub fn clear_sudo_password() {
+    *SUDO_PASSWORD
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Runs `command` with `input` and a newline on stdin, capturing stderr.
+fn run_with_input(command: &mut Command, input: &str) -> Result<std::process::Output> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {:?}: {}", command.get_program(), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // sudo doesn't read stdin when its credentials are still cached.
+        let _ = writeln!(stdin, "{}", input);
+    }
+    Ok(child.wait_with_output()?)
+}
+
+impl fmt::Display for Elevator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.program().unwrap_or("direct"))
+    }
+}
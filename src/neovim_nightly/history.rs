@@ -0,0 +1,219 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::{paths, update::InstallOutcome, version::NvimVersion, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    Install,
+    Build,
+    InstallFile,
+    Switch,
+    Rollback,
+    Remove,
+    Uninstall,
+}
+
+impl fmt::Display for HistoryAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            HistoryAction::Install => "install",
+            HistoryAction::Build => "build",
+            HistoryAction::InstallFile => "install-file",
+            HistoryAction::Switch => "switch",
+            HistoryAction::Rollback => "rollback",
+            HistoryAction::Remove => "remove",
+            HistoryAction::Uninstall => "uninstall",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOutcome {
+    Success,
+    Failed(String),
+    Cancelled,
+}
+
+/// One line of `history.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// RFC 3339, in UTC.
+    pub timestamp: String,
+    pub action: HistoryAction,
+    pub old_version: Option<NvimVersion>,
+    pub new_version: Option<NvimVersion>,
+    /// sha256 of the installed artifact, when there was one.
+    pub sha256: Option<String>,
+    pub path: Option<PathBuf>,
+    pub outcome: HistoryOutcome,
+}
+
+impl HistoryEntry {
+    pub fn new<T>(
+        action: HistoryAction,
+        old_version: Option<NvimVersion>,
+        new_version: Option<NvimVersion>,
+        result: &Result<T>,
+    ) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            action,
+            old_version,
+            new_version,
+            sha256: None,
+            path: None,
+            outcome: match result {
+                Ok(_) => HistoryOutcome::Success,
+                Err(e) => HistoryOutcome::Failed(e.to_string()),
+            },
+        }
+    }
+
+    /// The local time of the entry for display, falling back to the raw
+    /// timestamp when it can't be parsed.
+    pub fn local_time(&self) -> String {
+        chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.timestamp.clone())
+    }
+
+    /// A single line summary, e.g.
+    /// `2024-05-01 10:00:00  install  v0.10.1 -> v0.10.2  ok`.
+    pub fn summary(&self) -> String {
+        let version = |version: &Option<NvimVersion>| {
+            version
+                .as_ref()
+                .map_or("-".to_string(), NvimVersion::to_string)
+        };
+        let outcome = match &self.outcome {
+            HistoryOutcome::Success => "ok".to_string(),
+            HistoryOutcome::Failed(e) => format!("failed: {}", e),
+            HistoryOutcome::Cancelled => "cancelled".to_string(),
+        };
+        format!(
+            "{}  {:<12} {} -> {}  {}",
+            self.local_time(),
+            self.action.to_string(),
+            version(&self.old_version),
+            version(&self.new_version),
+            outcome
+        )
+    }
+}
+
+pub fn history_path() -> PathBuf {
+    paths::state_dir().join("history.jsonl")
+}
+
+/// Appends `entry` as one JSON line. Each entry is written with a single
+/// `write` to a file opened in append mode, so concurrent writers don't
+/// interleave within a line.
+pub fn record(entry: &HistoryEntry) -> Result<()> {
+    let path = history_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?
+        .write_all(&line)?;
+    Ok(())
+}
+
+/// Records the outcome of a version switch, rollback or removal. The history
+/// is best effort, so failing to write it is ignored.
+pub fn record_result<T>(
+    action: HistoryAction,
+    old_version: Option<NvimVersion>,
+    new_version: Option<NvimVersion>,
+    result: &Result<T>,
+) {
+    let _ = record(&HistoryEntry::new(action, old_version, new_version, result));
+}
+
+/// Records the result of an install. Failing to write the history doesn't
+/// fail the install; it is reported as a warning instead.
+pub fn record_install(
+    action: HistoryAction,
+    previous: Option<NvimVersion>,
+    result: &mut Result<InstallOutcome>,
+) {
+    let mut entry = HistoryEntry::new(action, previous, None, result);
+    if let Ok(outcome) = result.as_ref() {
+        entry.old_version = outcome.replaced.clone().or(entry.old_version);
+        entry.new_version = Some(outcome.version.clone());
+        entry.sha256 = outcome.sha256.clone();
+        entry.path = Some(outcome.target.clone());
+    }
+
+    if let Err(e) = record(&entry) {
+        if let Ok(outcome) = result.as_mut() {
+            outcome
+                .warnings
+                .push(format!("Failed to record the install history: {}", e));
+        }
+    }
+}
+
+/// An install in progress, recorded with `finish` once it returns, or as
+/// cancelled when it is dropped before that because its task was cancelled.
+pub struct PendingInstall {
+    action: HistoryAction,
+    previous: Option<NvimVersion>,
+    finished: bool,
+}
+
+impl PendingInstall {
+    pub fn new(action: HistoryAction, previous: Option<NvimVersion>) -> Self {
+        Self {
+            action,
+            previous,
+            finished: false,
+        }
+    }
+
+    pub fn finish(mut self, result: &mut Result<InstallOutcome>) {
+        self.finished = true;
+        record_install(self.action, self.previous.take(), result);
+    }
+}
+
+impl Drop for PendingInstall {
+    fn drop(&mut self) {
+        if !self.finished {
+            let mut entry = HistoryEntry::new(self.action, self.previous.take(), None, &Ok(()));
+            entry.outcome = HistoryOutcome::Cancelled;
+            let _ = record(&entry);
+        }
+    }
+}
+
+/// Every recorded entry, oldest first. Lines that can't be parsed, such as a
+/// line cut short by a crash, are skipped.
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    let contents = match std::fs::read_to_string(history_path()) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
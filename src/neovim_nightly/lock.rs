@@ -0,0 +1,132 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use super::{paths, Result};
+
+/// How many `spawn_blocking` jobs are running, and the files of the
+/// `InstallLock`s dropped meanwhile, which stay locked until none is.
+static BLOCKING: Mutex<(usize, Vec<File>)> = Mutex::new((0, Vec::new()));
+
+/// Advisory lock that serializes everything changing the installation, so
+/// e.g. a scheduled check and an interactive session can't install at the same
+/// time. The lock file records the holder's PID for the error message.
+///
+/// The lock is an `flock`, which the kernel drops when its holder exits, so a
+/// crashed instance never leaves it held; only the PID it wrote stays behind
+/// and is overwritten by the next holder.
+pub struct InstallLock {
+    file: Option<File>,
+}
+
+impl InstallLock {
+    pub fn path() -> PathBuf {
+        paths::state_dir().join("install.lock")
+    }
+
+    /// Takes the lock, failing right away with the holder's PID when another
+    /// operation holds it.
+    pub fn acquire() -> Result<InstallLock> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Err(held_error(&path, &mut file).into()),
+            Err(TryLockError::Error(e)) => {
+                return Err(format!("Failed to lock {}: {}", path.display(), e).into())
+            }
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(InstallLock { file: Some(file) })
+    }
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        let mut blocking = blocking();
+        if blocking.0 > 0 {
+            blocking.1.extend(self.file.take());
+        }
+    }
+}
+
+/// Like `tokio::task::spawn_blocking`, for work that changes the installation.
+/// Cancelling the task awaiting it can't stop `f`, so an `InstallLock` dropped
+/// while `f` runs is only released once `f` has returned.
+pub fn spawn_blocking<T, F>(f: F) -> tokio::task::JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    blocking().0 += 1;
+    let running = BlockingJob;
+    tokio::task::spawn_blocking(move || {
+        let _running = running;
+        f()
+    })
+}
+
+/// Counts a `spawn_blocking` job as running until it is dropped.
+struct BlockingJob;
+
+impl Drop for BlockingJob {
+    fn drop(&mut self) {
+        let mut blocking = blocking();
+        blocking.0 -= 1;
+        if blocking.0 == 0 {
+            blocking.1.clear();
+        }
+    }
+}
+
+fn blocking() -> MutexGuard<'static, (usize, Vec<File>)> {
+    BLOCKING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn held_error(path: &Path, file: &mut File) -> String {
+    let mut contents = String::new();
+    let holder = file
+        .read_to_string(&mut contents)
+        .ok()
+        .and_then(|_| contents.trim().parse::<u32>().ok());
+
+    match holder {
+        Some(pid) if pid == std::process::id() => {
+            "Another operation is still running in this neviraller; wait for it to finish"
+                .to_string()
+        }
+        Some(pid) if is_running(pid) => format!(
+            "Another neviraller (PID {}) is changing the installation; wait for it to finish",
+            pid
+        ),
+        // The recorded PID is stale; whatever holds the lock didn't record
+        // itself.
+        Some(pid) => format!(
+            "{} is held by another process (last recorded PID {} is no longer running)",
+            path.display(),
+            pid
+        ),
+        None => format!("{} is held by another process", path.display()),
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
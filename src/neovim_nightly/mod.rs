@@ -1,4 +1,23 @@
-pub mod scrap;
-pub mod ver_compare;
-pub mod update_offer;
+pub mod atomic;
+pub mod bundle;
+pub mod channel;
+pub mod checksum;
+pub mod config;
+pub mod download;
+pub mod elevate;
+pub mod history;
+pub mod lock;
+pub mod paths;
+pub mod plan;
+pub mod platform;
+pub mod release;
+pub mod source_build;
+pub mod store;
+#[cfg(test)]
+pub mod test_server;
+pub mod uninstall;
 pub mod update;
+pub mod ver_compare;
+pub mod version;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
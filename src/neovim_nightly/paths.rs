@@ -0,0 +1,71 @@
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+pub fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/"))
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(fallback))
+}
+
+pub fn cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache").join("neviraller")
+}
+
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config").join("neviraller")
+}
+
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state").join("neviraller")
+}
+
+/// Whether files can be created in `dir`, or in the nearest existing ancestor
+/// when `dir` itself doesn't exist yet. Asks `access(2)` rather than creating a
+/// probe file, so planning leaves the directory untouched.
+pub fn is_writable(dir: &Path) -> bool {
+    let Some(existing) = dir.ancestors().find(|ancestor| ancestor.exists()) else {
+        return false;
+    };
+    let Ok(path) = CString::new(existing.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    // SAFETY: `path` is a valid NUL-terminated string that outlives the call.
+    unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+}
+
+pub fn is_on_path(dir: &Path) -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|entry| entry == dir))
+        .unwrap_or(false)
+}
+
+pub fn data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share").join("neviraller")
+}
+
+/// Neovim's own config directory, where NEVIRAIDE lives.
+pub fn nvim_config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config").join("nvim")
+}
+
+/// Neovim's data directory, holding the plugins NEVIRAIDE installs.
+pub fn nvim_data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share").join("nvim")
+}
+
+pub fn nvim_state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state").join("nvim")
+}
+
+pub fn nvim_cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache").join("nvim")
+}
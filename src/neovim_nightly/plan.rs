@@ -0,0 +1,142 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use super::elevate::Elevator;
+
+/// One filesystem or network effect of an operation.
+#[derive(Debug, Clone)]
+pub enum Step {
+    Download {
+        url: String,
+        dest: PathBuf,
+        size: Option<u64>,
+    },
+    Verify {
+        file: PathBuf,
+        sha256: Option<String>,
+    },
+    Extract {
+        archive: PathBuf,
+        dest: PathBuf,
+    },
+    CreateDir(PathBuf),
+    Copy {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Chmod {
+        path: PathBuf,
+        mode: u32,
+    },
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Link {
+        link: PathBuf,
+        target: PathBuf,
+    },
+    Write(PathBuf),
+    Remove(PathBuf),
+    /// An installed version and the number of files its manifest lists.
+    RemoveVersion {
+        dir: PathBuf,
+        files: usize,
+    },
+    Run(String),
+    /// A step that can't run as things are, e.g. one that needs root when no
+    /// elevator is installed. Running the operation fails on it.
+    Blocked {
+        step: Box<Step>,
+        reason: String,
+    },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Download { url, dest, size } => {
+                write!(f, "download {} -> {}", url, dest.display())?;
+                if let Some(size) = size {
+                    write!(f, " ({:.1} MiB)", *size as f64 / (1024.0 * 1024.0))?;
+                }
+                Ok(())
+            }
+            Step::Verify { file, sha256 } => match sha256 {
+                Some(sha256) => write!(f, "verify sha256 of {} is {}", file.display(), sha256),
+                None => write!(f, "verify sha256 of {}", file.display()),
+            },
+            Step::Extract { archive, dest } => {
+                write!(f, "extract {} -> {}", archive.display(), dest.display())
+            }
+            Step::CreateDir(path) => write!(f, "mkdir -p {}", path.display()),
+            Step::Copy { from, to } => write!(f, "copy {} -> {}", from.display(), to.display()),
+            Step::Chmod { path, mode } => write!(f, "chmod {:o} {}", mode, path.display()),
+            Step::Move { from, to } => write!(f, "move {} -> {}", from.display(), to.display()),
+            Step::Link { link, target } => {
+                write!(f, "link {} -> {}", link.display(), target.display())
+            }
+            Step::Write(path) => write!(f, "write {}", path.display()),
+            Step::Remove(path) => write!(f, "remove {}", path.display()),
+            Step::RemoveVersion { dir, files } => {
+                write!(f, "remove {} ({} files)", dir.display(), files)
+            }
+            Step::Run(command) => write!(f, "run {}", command),
+            Step::Blocked { step, reason } => write!(f, "{} (will fail: {})", step, reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedStep {
+    pub step: Step,
+    pub elevator: Elevator,
+}
+
+/// What an install, rollback or uninstall is going to do, built before
+/// anything is touched so it can be confirmed or printed with `--dry-run`.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    pub title: String,
+    pub steps: Vec<PlannedStep>,
+}
+
+impl Plan {
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, step: Step) {
+        self.push_elevated(step, Elevator::Direct);
+    }
+
+    pub fn push_elevated(&mut self, step: Step, elevator: Elevator) {
+        self.steps.push(PlannedStep { step, elevator });
+    }
+
+    /// The elevator of the first step that runs as root, if any does.
+    pub fn elevator(&self) -> Option<Elevator> {
+        self.steps
+            .iter()
+            .map(|planned| planned.elevator)
+            .find(Elevator::is_elevated)
+    }
+
+    /// One line per step, the ones that run as root marked with their elevator,
+    /// e.g. `[sudo]`.
+    pub fn describe(&self) -> Vec<String> {
+        self.steps
+            .iter()
+            .map(|planned| {
+                if planned.elevator.is_elevated() {
+                    format!("[{}] {}", planned.elevator, planned.step)
+                } else {
+                    planned.step.to_string()
+                }
+            })
+            .collect()
+    }
+}
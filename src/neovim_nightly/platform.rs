@@ -0,0 +1,196 @@
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+use super::config::InstallFormat;
+
+pub fn has_program(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+/// Whether AppImages can be mounted: the kernel exposes `/dev/fuse` and a
+/// `fusermount` helper is installed.
+pub fn fuse_available() -> bool {
+    Path::new("/dev/fuse").exists() && (has_program("fusermount") || has_program("fusermount3"))
+}
+
+/// Runs a downloaded AppImage and reports whether it failed because FUSE is
+/// missing, which `fuse_available` can't always tell (e.g. only libfuse3 is
+/// installed).
+pub fn appimage_needs_fuse(appimage: &Path) -> bool {
+    match Command::new(appimage).arg("--version").output() {
+        Ok(output) if output.status.success() => false,
+        Ok(output) => String::from_utf8_lossy(&output.stderr)
+            .to_lowercase()
+            .contains("fuse"),
+        Err(_) => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arch {
+    X86_64,
+    Aarch64,
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Libc {
+    Glibc,
+    Musl,
+}
+
+/// The host the installer runs on, as far as it matters for picking a
+/// prebuilt release asset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    pub arch: Arch,
+    pub libc: Libc,
+}
+
+impl Platform {
+    pub fn detect() -> Platform {
+        Platform {
+            arch: detect_arch(),
+            libc: detect_libc(),
+        }
+    }
+
+    /// Release assets that run on this platform, newest naming scheme first.
+    /// Releases before v0.10.4 only shipped x86_64 builds as `nvim.appimage`
+    /// and `nvim-linux64.tar.gz`. Official builds link against glibc, so
+    /// there is nothing for musl systems.
+    pub fn asset_names(&self, format: InstallFormat) -> &'static [&'static str] {
+        match (&self.arch, self.libc, format) {
+            (_, Libc::Musl, _) => &[],
+            (Arch::X86_64, _, InstallFormat::Tarball) => {
+                &["nvim-linux-x86_64.tar.gz", "nvim-linux64.tar.gz"]
+            }
+            (Arch::X86_64, _, _) => &["nvim-linux-x86_64.appimage", "nvim.appimage"],
+            (Arch::Aarch64, _, InstallFormat::Tarball) => &["nvim-linux-arm64.tar.gz"],
+            (Arch::Aarch64, _, _) => &["nvim-linux-arm64.appimage"],
+            (Arch::Other(_), _, _) => &[],
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arch = match &self.arch {
+            Arch::X86_64 => "x86_64",
+            Arch::Aarch64 => "aarch64",
+            Arch::Other(arch) => arch,
+        };
+        let libc = match self.libc {
+            Libc::Glibc => "gnu",
+            Libc::Musl => "musl",
+        };
+        write!(f, "{}-linux-{}", arch, libc)
+    }
+}
+
+/// Asks `uname` rather than using the compile-time target, so an x86_64
+/// build running under emulation still picks the native asset.
+fn detect_arch() -> Arch {
+    let machine = Command::new("uname")
+        .arg("-m")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|machine| !machine.is_empty())
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string());
+
+    match machine.as_str() {
+        "x86_64" | "amd64" => Arch::X86_64,
+        "aarch64" | "arm64" => Arch::Aarch64,
+        _ => Arch::Other(machine),
+    }
+}
+
+/// musl's `ldd` names itself in its usage text; glibc's prints its version.
+/// Without `ldd`, fall back to looking for the musl dynamic loader.
+fn detect_libc() -> Libc {
+    if let Ok(output) = Command::new("ldd").arg("--version").output() {
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .to_lowercase();
+        if text.contains("musl") {
+            return Libc::Musl;
+        }
+        if text.contains("glibc") || text.contains("gnu libc") {
+            return Libc::Glibc;
+        }
+    }
+
+    let has_musl_loader = std::fs::read_dir("/lib").is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-"))
+    });
+    if has_musl_loader {
+        Libc::Musl
+    } else {
+        Libc::Glibc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_platforms_to_asset_names() {
+        let cases: [(Arch, Libc, InstallFormat, &[&str]); 8] = [
+            (
+                Arch::X86_64,
+                Libc::Glibc,
+                InstallFormat::Tarball,
+                &["nvim-linux-x86_64.tar.gz", "nvim-linux64.tar.gz"],
+            ),
+            (
+                Arch::X86_64,
+                Libc::Glibc,
+                InstallFormat::AppImage,
+                &["nvim-linux-x86_64.appimage", "nvim.appimage"],
+            ),
+            (
+                Arch::Aarch64,
+                Libc::Glibc,
+                InstallFormat::Tarball,
+                &["nvim-linux-arm64.tar.gz"],
+            ),
+            (
+                Arch::Aarch64,
+                Libc::Glibc,
+                InstallFormat::AppImage,
+                &["nvim-linux-arm64.appimage"],
+            ),
+            (Arch::X86_64, Libc::Musl, InstallFormat::Tarball, &[]),
+            (Arch::X86_64, Libc::Musl, InstallFormat::AppImage, &[]),
+            (Arch::Aarch64, Libc::Musl, InstallFormat::Tarball, &[]),
+            (
+                Arch::Other("riscv64".to_string()),
+                Libc::Glibc,
+                InstallFormat::Tarball,
+                &[],
+            ),
+        ];
+
+        for (arch, libc, format, expected) in cases {
+            let platform = Platform { arch, libc };
+            assert_eq!(
+                platform.asset_names(format),
+                expected,
+                "{} {:?}",
+                platform,
+                format
+            );
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{channel::Channel, version::NvimVersion, Result};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+const NEOVIM_REPO: &str = "neovim/neovim";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub body: Option<String>,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub size: u64,
+    pub browser_download_url: String,
+    pub digest: Option<String>,
+}
+
+impl Release {
+    /// Version as printed by `nvim --version`, taken from the `NVIM ...` line of
+    /// the release notes. Falls back to the tag for releases without it.
+    pub fn version(&self) -> Option<NvimVersion> {
+        self.body
+            .as_deref()
+            .and_then(|body| {
+                body.lines()
+                    .map(str::trim)
+                    .find(|line| line.starts_with("NVIM "))
+            })
+            .and_then(|line| line.parse().ok())
+            .or_else(|| self.tag_name.parse().ok())
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+pub struct ReleaseSource {
+    client: reqwest::Client,
+    base_url: String,
+    repo: String,
+}
+
+impl ReleaseSource {
+    pub fn new() -> Result<Self> {
+        Self::with_base_url(GITHUB_API_URL)
+    }
+
+    /// A source talking to another GitHub API endpoint, e.g. a stand-in
+    /// server in tests.
+    pub fn with_base_url(base_url: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("neviraller/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("Failed to build the HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            repo: NEOVIM_REPO.to_string(),
+        })
+    }
+
+    pub async fn release(&self, channel: &Channel) -> Result<Release> {
+        self.release_by_tag(channel.tag()).await
+    }
+
+    pub async fn release_by_tag(&self, tag: &str) -> Result<Release> {
+        self.get(&format!("releases/tags/{}", tag)).await
+    }
+
+    pub async fn releases(&self, per_page: u8) -> Result<Vec<Release>> {
+        self.get(&format!("releases?per_page={}", per_page)).await
+    }
+
+    /// The full hash of the commit `git_ref`, a tag, branch or commit, points
+    /// at.
+    pub async fn commit(&self, git_ref: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Commit {
+            sha: String,
+        }
+
+        let commit: Commit = self.get(&format!("commits/{}", git_ref)).await?;
+        Ok(commit.sha)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}/repos/{}/{}", self.base_url, self.repo, path);
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "GitHub API request to {} failed: {}",
+                url,
+                response.status()
+            )
+            .into());
+        }
+
+        Ok(response.json::<T>().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neovim_nightly::test_server;
+
+    const NIGHTLY: &str = r#"{
+        "tag_name": "nightly",
+        "body": "```\nNVIM v0.11.0-dev-1234+g0123abcd\nBuild type: RelWithDebInfo\n```",
+        "assets": [{
+            "name": "nvim-linux64.tar.gz",
+            "size": 11,
+            "browser_download_url": "https://example.invalid/nvim-linux64.tar.gz",
+            "digest": "sha256:00"
+        }]
+    }"#;
+
+    fn source(base_url: &str) -> ReleaseSource {
+        ReleaseSource::with_base_url(base_url).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetches_a_release_by_channel() {
+        let server = test_server::serve(|request| {
+            if request.starts_with("GET /repos/neovim/neovim/releases/tags/nightly ") {
+                test_server::response("200 OK", &[], NIGHTLY.as_bytes())
+            } else {
+                test_server::response("404 Not Found", &[], b"{}")
+            }
+        })
+        .await;
+
+        let release = source(&server).release(&Channel::Nightly).await.unwrap();
+        assert_eq!(release.tag_name, "nightly");
+        assert_eq!(
+            release.version(),
+            Some("v0.11.0-dev-1234+g0123abcd".parse().unwrap())
+        );
+        let asset = release.asset("nvim-linux64.tar.gz").unwrap();
+        assert_eq!(asset.size, 11);
+        assert_eq!(asset.digest.as_deref(), Some("sha256:00"));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_tag_for_the_version() {
+        let server = test_server::serve(|_| {
+            let body = r#"{"tag_name": "v0.10.2", "body": null, "assets": []}"#;
+            test_server::response("200 OK", &[], body.as_bytes())
+        })
+        .await;
+
+        let release = source(&server).release_by_tag("v0.10.2").await.unwrap();
+        assert_eq!(release.version(), Some("v0.10.2".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn resolves_a_ref_to_its_commit() {
+        let server = test_server::serve(|request| {
+            if request.starts_with("GET /repos/neovim/neovim/commits/nightly ") {
+                let body = r#"{"sha": "0123abcd4567ef890123abcd4567ef890123abcd"}"#;
+                test_server::response("200 OK", &[], body.as_bytes())
+            } else {
+                test_server::response("404 Not Found", &[], b"{}")
+            }
+        })
+        .await;
+
+        let commit = source(&server).commit("nightly").await.unwrap();
+        assert_eq!(commit, "0123abcd4567ef890123abcd4567ef890123abcd");
+    }
+
+    #[tokio::test]
+    async fn reports_failed_requests() {
+        let server =
+            test_server::serve(|_| test_server::response("404 Not Found", &[], b"{}")).await;
+
+        let error = source(&server)
+            .release_by_tag("v9.9.9")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("releases/tags/v9.9.9"), "{}", error);
+        assert!(error.contains("404"), "{}", error);
+    }
+}
@@ -0,0 +1,324 @@
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+use std::time::{Duration, Instant};
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader},
+    process::{Child, Command},
+    sync::mpsc::UnboundedSender,
+};
+
+use super::{
+    atomic::{self, RemoveOnDrop},
+    config::BuildConfig,
+    download::{download, Progress},
+    lock, paths,
+    plan::{Plan, Step},
+    platform,
+    release::ReleaseSource,
+    store::{extract_tarball, VersionStore},
+    version::NvimVersion,
+    Result,
+};
+
+const SOURCE_ARCHIVE_URL: &str = "https://github.com/neovim/neovim/archive";
+
+/// Length of the abbreviated commit added to the version of development builds.
+const COMMIT_LEN: usize = 9;
+
+/// How long a cancelled build gets to exit once it has been killed.
+const KILL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Programs a source build runs.
+pub const BUILD_DEPENDENCIES: [&str; 4] = ["cmake", "make", "gcc", "gettext"];
+
+/// Builds Neovim from the GitHub source archive of a git ref (a release tag,
+/// branch or commit) and installs the result into the version store.
+pub struct SourceBuild {
+    pub git_ref: String,
+    pub config: BuildConfig,
+}
+
+impl SourceBuild {
+    pub fn new(git_ref: &str, config: &BuildConfig) -> Self {
+        Self {
+            git_ref: config.git_ref.clone().unwrap_or(git_ref.to_string()),
+            config: config.clone(),
+        }
+    }
+
+    pub fn missing_dependencies() -> Vec<&'static str> {
+        BUILD_DEPENDENCIES
+            .into_iter()
+            .filter(|program| !platform::has_program(program))
+            .collect()
+    }
+
+    /// Downloads, builds and installs the source. Every line the build prints
+    /// is sent to `log`. Returns the version of the built binary.
+    pub async fn install(
+        &self,
+        progress: Option<&UnboundedSender<Progress>>,
+        log: &UnboundedSender<String>,
+    ) -> Result<NvimVersion> {
+        let missing = Self::missing_dependencies();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Building Neovim needs {}; install them and try again",
+                missing.join(", ")
+            )
+            .into());
+        }
+
+        let (archive, source_dir) = self.work_paths();
+        let cleanup = RemoveOnDrop::new([archive.clone(), source_dir.clone()]);
+
+        // The commit is pinned first: the source archive has no git history
+        // for the build to describe itself with, and a moving ref such as
+        // `nightly` must not change between the lookup and the download.
+        let commit = ReleaseSource::new()?
+            .commit(&self.git_ref)
+            .await
+            .map_err(|e| format!("Failed to look up {}: {}", self.git_ref, e))?;
+
+        let _ = log.send(format!(
+            "Downloading Neovim source {} ({})",
+            self.git_ref, commit
+        ));
+        download(&archive_url(&commit), &archive, progress)
+            .await
+            .map_err(|e| format!("Failed to download the source of {}: {}", self.git_ref, e))?;
+
+        let (extract_from, extract_to) = (archive.clone(), source_dir.clone());
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let _ = std::fs::remove_dir_all(&extract_to);
+            std::fs::create_dir_all(&extract_to)?;
+            extract_tarball(&extract_from, &extract_to)?;
+            Ok(())
+        })
+        .await??;
+        let _ = tokio::fs::remove_file(&archive).await;
+
+        let result = self.build_and_install(&source_dir, &commit, log).await;
+        cleanup.disarm();
+        let _ = tokio::fs::remove_dir_all(&source_dir).await;
+        result
+    }
+
+    async fn build_and_install(
+        &self,
+        source_dir: &Path,
+        commit: &str,
+        log: &UnboundedSender<String>,
+    ) -> Result<NvimVersion> {
+        let mut make = process::Command::new("make");
+        make.current_dir(source_dir).args(self.make_args());
+        run_logged(make, log).await?;
+
+        let build_dir = source_dir.join("build");
+        let mut version = atomic::validate_binary(&build_dir.join("bin").join("nvim"))?;
+        // Development builds of different commits otherwise share a version,
+        // and with it a store directory.
+        if version.prerelease.is_some() && version.commit.is_none() {
+            version.commit = Some(commit[..commit.len().min(COMMIT_LEN)].to_string());
+        }
+        let _ = log.send(format!("Built Neovim {}", version));
+
+        let store = VersionStore::new();
+        let staged = store.stage_build(&version)?;
+        let cleanup = RemoveOnDrop::new([staged.clone()]);
+        let mut install = process::Command::new("cmake");
+        install
+            .arg("--install")
+            .arg(&build_dir)
+            .arg("--prefix")
+            .arg(&staged);
+        let installed = run_logged(install, log).await;
+        cleanup.disarm();
+        if let Err(e) = installed {
+            let _ = tokio::fs::remove_dir_all(&staged).await;
+            return Err(e);
+        }
+
+        let add_version = version.clone();
+        lock::spawn_blocking(move || store.add_build(&add_version)).await??;
+
+        Ok(version)
+    }
+
+    /// Adds the steps `install` takes to `plan`, with the build installed into
+    /// `version_dir` of the store.
+    pub fn plan(&self, plan: &mut Plan, version_dir: &Path) {
+        let (archive, source_dir) = self.work_paths();
+        let build_dir = source_dir.join("build");
+        let staged = atomic::staged_path(version_dir);
+
+        plan.push(Step::Download {
+            url: archive_url(&format!("<commit of {}>", self.git_ref)),
+            dest: archive.clone(),
+            size: None,
+        });
+        plan.push(Step::Extract {
+            archive: archive.clone(),
+            dest: source_dir.clone(),
+        });
+        plan.push(Step::Remove(archive));
+        plan.push(Step::Run(format!(
+            "make {} in {}",
+            self.make_args().join(" "),
+            source_dir.display()
+        )));
+        plan.push(Step::Run(format!(
+            "cmake --install {} --prefix {}",
+            build_dir.display(),
+            staged.display()
+        )));
+        plan.push(Step::Move {
+            from: staged,
+            to: version_dir.to_path_buf(),
+        });
+        plan.push(Step::Remove(source_dir));
+    }
+
+    /// Where the source archive is downloaded to and unpacked into.
+    fn work_paths(&self) -> (PathBuf, PathBuf) {
+        let name = self.git_ref.replace('/', "-");
+        let archive = paths::cache_dir()
+            .join("sources")
+            .join(format!("{}.tar.gz", name));
+        (archive, paths::cache_dir().join("build").join(&name))
+    }
+
+    fn make_args(&self) -> Vec<String> {
+        let mut args = vec![format!(
+            "CMAKE_BUILD_TYPE={}",
+            self.config.build_type.as_str()
+        )];
+        if !self.config.cmake_flags.is_empty() {
+            args.push(format!(
+                "CMAKE_EXTRA_FLAGS={}",
+                self.config.cmake_flags.join(" ")
+            ));
+        }
+        args
+    }
+}
+
+fn archive_url(git_ref: &str) -> String {
+    format!("{}/{}.tar.gz", SOURCE_ARCHIVE_URL, git_ref)
+}
+
+/// Runs `command`, sending its stdout and stderr to `log` line by line as they
+/// are printed.
+async fn run_logged(mut command: process::Command, log: &UnboundedSender<String>) -> Result<()> {
+    // In a group of its own, so cancelling kills the compilers `make` started
+    // along with it.
+    command.process_group(0);
+    let mut command = Command::from(command);
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to start {:?}: {}",
+                command.as_std().get_program(),
+                e
+            )
+        })?;
+    let mut group = ProcessGroup(child);
+
+    let stdout = group.0.stdout.take().map(BufReader::new);
+    let stderr = group.0.stderr.take().map(BufReader::new);
+    tokio::join!(forward_lines(stdout, log), forward_lines(stderr, log));
+
+    let status = group.0.wait().await?;
+    if !status.success() {
+        return Err(format!("{:?} failed ({})", command.as_std().get_program(), status).into());
+    }
+    Ok(())
+}
+
+/// A child leading its own process group. Dropping it before the child has
+/// exited kills the whole group and waits for it, so nothing is left writing
+/// into the source directory that is removed next.
+struct ProcessGroup(Child);
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if !matches!(self.0.try_wait(), Ok(None)) {
+            return;
+        }
+        let Some(pgid) = self.0.id().map(|pid| pid as libc::pid_t) else {
+            return;
+        };
+
+        // SAFETY: `killpg` only sends a signal; the group is the child's own.
+        unsafe { libc::killpg(pgid, libc::SIGKILL) };
+        let deadline = Instant::now() + KILL_TIMEOUT;
+        while Instant::now() < deadline
+            && (matches!(self.0.try_wait(), Ok(None)) || group_is_running(pgid))
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Whether a process of group `pgid` is still running. Members that have
+/// exited but weren't reaped yet don't count.
+fn group_is_running(pgid: libc::pid_t) -> bool {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            return false;
+        };
+        // `pid (comm) state ppid pgrp ...`; `comm` may contain anything.
+        let Some((_, fields)) = stat.rsplit_once(')') else {
+            return false;
+        };
+        let mut fields = fields.split_whitespace();
+        let state = fields.next();
+        let pgrp = fields
+            .nth(1)
+            .and_then(|pgrp| pgrp.parse::<libc::pid_t>().ok());
+        pgrp == Some(pgid) && state != Some("Z")
+    })
+}
+
+async fn forward_lines(reader: Option<impl AsyncBufRead + Unpin>, log: &UnboundedSender<String>) {
+    let Some(reader) = reader else {
+        return;
+    };
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = log.send(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn kills_the_whole_build_when_cancelled() {
+        let (log, mut lines) = tokio::sync::mpsc::unbounded_channel();
+        let mut command = process::Command::new("sh");
+        command.args(["-c", "sleep 30 & echo $!; wait"]);
+
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(500), run_logged(command, &log)).await;
+
+        assert!(cancelled.is_err());
+        let grandchild: libc::pid_t = lines.recv().await.unwrap().parse().unwrap();
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", grandchild));
+        assert!(
+            stat.map_or(true, |stat| stat.contains(") Z ")),
+            "sleep {} is still running",
+            grandchild
+        );
+    }
+}
@@ -0,0 +1,732 @@
+use std::fs::File;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use flate2::read::GzDecoder;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    atomic, paths,
+    plan::{Plan, Step},
+    version::NvimVersion,
+    Result,
+};
+
+pub struct InstalledVersion {
+    pub version: NvimVersion,
+    pub path: PathBuf,
+    pub current: bool,
+}
+
+/// Files a version directory consists of, relative to that directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VersionManifest {
+    pub files: Vec<PathBuf>,
+}
+
+/// What the last switch replaced, so it can be rolled back, and the links
+/// created outside the store, so they can be uninstalled.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreState {
+    previous: Option<NvimVersion>,
+    #[serde(default)]
+    links: Vec<PathBuf>,
+}
+
+/// What a Neovim install keeps next to `bin/nvim` under its prefix.
+pub const PREFIX_DIRS: [&str; 2] = ["share/nvim", "lib/nvim"];
+
+/// Side-by-side Neovim builds under `<data dir>/versions/<version>/`, with a
+/// `current` symlink pointing at the active one.
+pub struct VersionStore {
+    root: PathBuf,
+}
+
+impl VersionStore {
+    pub fn new() -> Self {
+        Self {
+            root: paths::data_dir(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn versions_dir(&self) -> PathBuf {
+        self.root.join("versions")
+    }
+
+    pub fn version_dir(&self, version: &NvimVersion) -> PathBuf {
+        self.versions_dir().join(version.to_string())
+    }
+
+    fn manifest_path(version_dir: &Path) -> PathBuf {
+        version_dir.join(".neviraller-manifest.json")
+    }
+
+    fn write_manifest(version_dir: &Path, files: Vec<PathBuf>) -> Result<()> {
+        let manifest = VersionManifest { files };
+        std::fs::write(
+            Self::manifest_path(version_dir),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn manifest(&self, version: &NvimVersion) -> Result<VersionManifest> {
+        let contents = std::fs::read(Self::manifest_path(&self.version_dir(version)))?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        self.root.join("state.json")
+    }
+
+    pub fn current_link(&self) -> PathBuf {
+        self.root.join("current")
+    }
+
+    /// Path of the active `nvim` through the `current` symlink, which is what
+    /// the install prefix links to so switching never touches the prefix.
+    pub fn current_binary(&self) -> PathBuf {
+        self.current_link().join("bin").join("nvim")
+    }
+
+    pub fn contains(&self, version: &NvimVersion) -> bool {
+        self.version_dir(version).join("bin").join("nvim").exists()
+    }
+
+    pub fn current(&self) -> Result<Option<NvimVersion>> {
+        match std::fs::read_link(self.current_link()) {
+            Ok(target) => Ok(target
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.parse().ok())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The version that was active before the last switch, if it is still
+    /// installed.
+    pub fn previous(&self) -> Result<Option<NvimVersion>> {
+        let state = self.read_state()?;
+        Ok(state.previous.filter(|previous| self.contains(previous)))
+    }
+
+    fn read_state(&self) -> Result<StoreState> {
+        match std::fs::read(self.state_path()) {
+            Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StoreState::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Remembers a symlink to the store created elsewhere, e.g.
+    /// `<prefix>/bin/nvim`.
+    pub fn record_link(&self, link: &Path) -> Result<()> {
+        let mut state = self.read_state()?;
+        if !state.links.iter().any(|recorded| recorded == link) {
+            state.links.push(link.to_path_buf());
+            self.write_state(&state)?;
+        }
+        Ok(())
+    }
+
+    pub fn links(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.read_state()?.links)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn write_state(&self, state: &StoreState) -> Result<()> {
+        let staged = self.root.join(".state.json.new");
+        std::fs::write(&staged, serde_json::to_vec_pretty(state)?)?;
+        std::fs::rename(staged, self.state_path())?;
+        Ok(())
+    }
+
+    pub fn list(&self) -> Result<Vec<InstalledVersion>> {
+        let current = self.current()?;
+        let entries = match std::fs::read_dir(self.versions_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut versions = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let Some(version) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<NvimVersion>().ok())
+            else {
+                continue;
+            };
+            versions.push(InstalledVersion {
+                current: current.as_ref() == Some(&version),
+                path: entry.path(),
+                version,
+            });
+        }
+        versions.sort_by(|a, b| b.version.cmp(&a.version));
+
+        Ok(versions)
+    }
+
+    /// Installs a single-file `nvim` (an AppImage) into the store as
+    /// `<version>/bin/nvim`. The binary is validated before it replaces an
+    /// existing one.
+    pub fn add_binary(&self, version: &NvimVersion, source: &Path) -> Result<PathBuf> {
+        let version_dir = self.version_dir(version);
+        let existed = version_dir.exists();
+        let bin_dir = version_dir.join("bin");
+        std::fs::create_dir_all(&bin_dir)?;
+
+        let binary = bin_dir.join("nvim");
+        if let Err(e) = atomic::replace_file(source, &binary) {
+            if !existed {
+                let _ = std::fs::remove_dir_all(&version_dir);
+            }
+            return Err(e);
+        }
+        Self::write_manifest(&version_dir, vec![Path::new("bin").join("nvim")])?;
+
+        Ok(binary)
+    }
+
+    /// Copies a Neovim that was installed into `prefix` without the store, e.g.
+    /// from a release tarball, as `<version>/{bin,lib,share}`: `bin/nvim` with
+    /// the `share/nvim` runtime and `lib/nvim` parsers it finds relative to
+    /// itself, when there are any. Returns every copied file.
+    pub fn add_prefix_install(&self, version: &NvimVersion, prefix: &Path) -> Result<Vec<PathBuf>> {
+        self.add_tree(version, |staged| {
+            std::fs::create_dir_all(staged.join("bin"))?;
+            std::fs::copy(
+                prefix.join("bin").join("nvim"),
+                staged.join("bin").join("nvim"),
+            )?;
+            for dir in PREFIX_DIRS {
+                let from = prefix.join(dir);
+                if from.is_dir() {
+                    copy_tree(&from, &staged.join(dir))?;
+                }
+            }
+            list_files(staged, staged)
+        })
+    }
+
+    /// Extracts an official `nvim-linux*.tar.gz` into the store as
+    /// `<version>/{bin,lib,share}`. Returns every extracted file.
+    pub fn add_tarball(&self, version: &NvimVersion, archive: &Path) -> Result<Vec<PathBuf>> {
+        self.add_tree(version, |staged| extract_tarball(archive, staged))
+    }
+
+    /// Unpacks an AppImage with `--appimage-extract`, for systems where it
+    /// can't be mounted, and links `bin/nvim` to the extracted
+    /// `squashfs-root/usr/bin/nvim`. Returns every extracted file.
+    pub fn add_extracted_appimage(
+        &self,
+        version: &NvimVersion,
+        appimage: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        self.add_tree(version, |staged| {
+            let copy = staged.join("nvim.appimage");
+            std::fs::copy(appimage, &copy)?;
+            std::fs::set_permissions(&copy, std::fs::Permissions::from_mode(0o755))?;
+            let output = Command::new(&copy)
+                .arg("--appimage-extract")
+                .current_dir(staged)
+                .output()?;
+            std::fs::remove_file(&copy)?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to extract {}: {}",
+                    appimage.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+                .into());
+            }
+
+            std::fs::create_dir_all(staged.join("bin"))?;
+            symlink(
+                Path::new("../squashfs-root/usr/bin/nvim"),
+                staged.join("bin").join("nvim"),
+            )?;
+
+            let mut files = list_files(staged, &staged.join("squashfs-root"))?;
+            files.push(Path::new("bin").join("nvim"));
+            Ok(files)
+        })
+    }
+
+    /// An empty staging directory for a Neovim built from source, which the
+    /// build installs its usual `bin/`, `lib/` and `share/` tree into before
+    /// `add_build` moves it into place.
+    pub fn stage_build(&self, version: &NvimVersion) -> Result<PathBuf> {
+        std::fs::create_dir_all(self.versions_dir())?;
+        let staged = atomic::staged_path(&self.version_dir(version));
+        let _ = std::fs::remove_dir_all(&staged);
+        std::fs::create_dir_all(&staged)?;
+        Ok(staged)
+    }
+
+    /// Installs the build in the directory from `stage_build`. Returns every
+    /// installed file.
+    pub fn add_build(&self, version: &NvimVersion) -> Result<Vec<PathBuf>> {
+        let staged = atomic::staged_path(&self.version_dir(version));
+        self.add_staged(version, &staged, |staged| list_files(staged, staged))
+    }
+
+    /// Fills a staging directory with `fill`, validates its `bin/nvim` and only
+    /// then moves it into place as the directory of `version`.
+    fn add_tree(
+        &self,
+        version: &NvimVersion,
+        fill: impl FnOnce(&Path) -> Result<Vec<PathBuf>>,
+    ) -> Result<Vec<PathBuf>> {
+        let staged = self.stage_build(version)?;
+        self.add_staged(version, &staged, fill)
+    }
+
+    fn add_staged(
+        &self,
+        version: &NvimVersion,
+        staged: &Path,
+        fill: impl FnOnce(&Path) -> Result<Vec<PathBuf>>,
+    ) -> Result<Vec<PathBuf>> {
+        let version_dir = self.version_dir(version);
+        let filled = (|| {
+            let files = fill(staged)?;
+            atomic::validate_binary(&staged.join("bin").join("nvim"))?;
+            Self::write_manifest(staged, files.clone())?;
+            Ok(files)
+        })();
+        let files = match filled {
+            Ok(files) => files,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(staged);
+                return Err(e);
+            }
+        };
+
+        if version_dir.exists() {
+            let old =
+                version_dir.with_file_name(format!(".{}.old-{}", version, std::process::id()));
+            std::fs::rename(&version_dir, &old)?;
+            std::fs::rename(staged, &version_dir)?;
+            std::fs::remove_dir_all(&old)?;
+        } else {
+            std::fs::rename(staged, &version_dir)?;
+        }
+
+        Ok(files)
+    }
+
+    /// Makes `version` the active one and returns the version it replaced.
+    pub fn switch(&self, version: &NvimVersion) -> Result<Option<NvimVersion>> {
+        if !self.contains(version) {
+            return Err(format!("Neovim {} is not installed", version).into());
+        }
+
+        let replaced = self.current()?;
+        if replaced.as_ref() == Some(version) {
+            return Ok(None);
+        }
+
+        // Build the new link next to the old one, check that it starts and
+        // rename it over, so `current` always points at a working version.
+        let staged = atomic::staged_path(&self.current_link());
+        let _ = std::fs::remove_file(&staged);
+        symlink(Path::new("versions").join(version.to_string()), &staged)?;
+        if let Err(e) = atomic::validate_binary(&staged.join("bin").join("nvim")) {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e);
+        }
+        std::fs::rename(&staged, self.current_link())?;
+
+        if replaced.is_some() {
+            let mut state = self.read_state()?;
+            state.previous = replaced.clone();
+            self.write_state(&state)?;
+        }
+
+        Ok(replaced)
+    }
+
+    /// Adds the steps `switch` takes to `plan`. `None` stands for a version
+    /// that is only known once it has been built or probed.
+    pub fn plan_switch(&self, plan: &mut Plan, version: Option<&NvimVersion>) {
+        let current = self.current().ok().flatten();
+        if version.is_some() && current.as_ref() == version {
+            return;
+        }
+
+        let staged = atomic::staged_path(&self.current_link());
+        plan.push(Step::Link {
+            link: staged.clone(),
+            target: Path::new("versions").join(Self::planned_name(version)),
+        });
+        plan.push(Step::Move {
+            from: staged,
+            to: self.current_link(),
+        });
+        if current.is_some() {
+            plan.push(Step::Write(self.state_path()));
+        }
+    }
+
+    /// Directory `version` is installed into, see `plan_switch` for `None`.
+    pub fn planned_version_dir(&self, version: Option<&NvimVersion>) -> PathBuf {
+        self.versions_dir().join(Self::planned_name(version))
+    }
+
+    fn planned_name(version: Option<&NvimVersion>) -> String {
+        version.map_or("<version>".to_string(), NvimVersion::to_string)
+    }
+
+    /// The steps `rollback` would take.
+    pub fn plan_rollback(&self) -> Result<Plan> {
+        let previous = self
+            .previous()?
+            .ok_or("There is no previous Neovim version to roll back to")?;
+        let current = self
+            .current()?
+            .ok_or("There is no active Neovim version to roll back from")?;
+
+        let mut plan = Plan::new(format!("Roll back from Neovim {} to {}", current, previous));
+        self.plan_switch(&mut plan, Some(&previous));
+        Ok(plan)
+    }
+
+    /// Switches back to the version active before the last switch. Returns the
+    /// versions rolled back from and to.
+    pub fn rollback(&self) -> Result<(NvimVersion, NvimVersion)> {
+        let previous = self
+            .previous()?
+            .ok_or("There is no previous Neovim version to roll back to")?;
+        let replaced = self
+            .switch(&previous)?
+            .ok_or("There is no active Neovim version to roll back from")?;
+
+        Ok((replaced, previous))
+    }
+
+    pub fn remove(&self, version: &NvimVersion) -> Result<()> {
+        if self.current()?.as_ref() == Some(version) {
+            return Err(format!(
+                "Neovim {} is the active version; switch to another one first",
+                version
+            )
+            .into());
+        }
+
+        let dir = self.version_dir(version);
+        if !dir.exists() {
+            return Err(format!("Neovim {} is not installed", version).into());
+        }
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
+
+/// Unpacks `archive` into `dest`, dropping the archive's top-level directory
+/// (e.g. `nvim-linux64/`).
+///
+/// Archives also come from the user (local files, bundles), so nothing is
+/// written outside `dest`: hard links and symlinks pointing out of it are
+/// refused, and so is writing through a symlink an earlier entry created.
+pub fn extract_tarball(archive: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+    let mut tarball = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+    let mut files = Vec::new();
+    std::fs::create_dir_all(dest)?;
+
+    for entry in tarball.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let relative: PathBuf = path.components().skip(1).collect();
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+        {
+            return Err(format!("Refusing to extract {}", path.display()).into());
+        }
+
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_hard_link() {
+            return Err(format!("Refusing to extract the hard link {}", path.display()).into());
+        }
+        if entry_type.is_symlink() {
+            let link = entry.link_name()?.unwrap_or_default();
+            if !link_stays_inside(&relative, &link) {
+                return Err(format!(
+                    "Refusing to extract {}, which links to {} outside the archive",
+                    path.display(),
+                    link.display()
+                )
+                .into());
+            }
+        }
+
+        if entry_type.is_dir() {
+            create_dirs(dest, &relative)?;
+            continue;
+        }
+        create_dirs(dest, relative.parent().unwrap_or(Path::new("")))?;
+        entry.unpack(dest.join(&relative))?;
+        files.push(relative);
+    }
+
+    Ok(files)
+}
+
+/// Creates `relative` below `dest` one directory at a time, failing when any
+/// part of it already exists as something other than a directory, e.g. a
+/// symlink an earlier entry created.
+fn create_dirs(dest: &Path, relative: &Path) -> Result<()> {
+    let mut dir = dest.to_path_buf();
+    for component in relative.components() {
+        dir.push(component);
+        match std::fs::symlink_metadata(&dir) {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => {
+                return Err(format!(
+                    "Refusing to extract into {}, which is not a directory",
+                    dir.display()
+                )
+                .into())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => std::fs::create_dir(&dir)?,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Whether a symlink at `relative` pointing to `link` resolves below the
+/// directory `relative` is in.
+pub fn link_stays_inside(relative: &Path, link: &Path) -> bool {
+    let mut depth = relative.components().count().saturating_sub(1);
+    for component in link.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Copies the directory `from` to `to`, keeping symlinks as they are.
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let (source, target) = (entry.path(), to.join(entry.file_name()));
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_tree(&source, &target)?;
+        } else if file_type.is_symlink() {
+            symlink(std::fs::read_link(&source)?, &target)?;
+        } else {
+            std::fs::copy(&source, &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every file and symlink below `dir`, relative to `root`.
+fn list_files(root: &Path, dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            files.extend(list_files(root, &path)?);
+        } else {
+            files.push(path.strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+    use tar::{Builder, EntryType, Header};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "neviraller-store-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    enum Entry<'a> {
+        File(&'a str, &'a [u8]),
+        Symlink(&'a str, &'a str),
+        HardLink(&'a str, &'a str),
+    }
+
+    /// Writes a `.tar.gz` with `entries` below `dir/archive.tar.gz`.
+    fn tarball(dir: &Path, entries: &[Entry]) -> PathBuf {
+        let archive = dir.join("archive.tar.gz");
+        let mut builder = Builder::new(GzEncoder::new(
+            File::create(&archive).unwrap(),
+            Compression::fast(),
+        ));
+        for entry in entries {
+            let mut header = Header::new_gnu();
+            header.set_mode(0o644);
+            let (path, data, link) = match entry {
+                Entry::File(path, data) => {
+                    header.set_entry_type(EntryType::Regular);
+                    (path, *data, None)
+                }
+                Entry::Symlink(path, target) => {
+                    header.set_entry_type(EntryType::Symlink);
+                    (path, &b""[..], Some(target))
+                }
+                Entry::HardLink(path, target) => {
+                    header.set_entry_type(EntryType::Link);
+                    (path, &b""[..], Some(target))
+                }
+            };
+            header.set_size(data.len() as u64);
+            match link {
+                Some(target) => builder.append_link(&mut header, path, target).unwrap(),
+                None => builder.append_data(&mut header, path, data).unwrap(),
+            }
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        archive
+    }
+
+    #[test]
+    fn adopts_a_prefix_install_with_its_runtime() {
+        let dir = temp_dir("adopt");
+        let prefix = dir.join("prefix");
+        for path in ["bin", "share/nvim/runtime", "lib/nvim/parser"] {
+            std::fs::create_dir_all(prefix.join(path)).unwrap();
+        }
+        let binary = prefix.join("bin").join("nvim");
+        std::fs::write(&binary, "#!/bin/sh\necho 'NVIM v0.9.5'\n").unwrap();
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(prefix.join("share/nvim/runtime/filetype.lua"), "lua").unwrap();
+        std::fs::write(prefix.join("lib/nvim/parser/c.so"), "parser").unwrap();
+
+        let store = VersionStore::with_root(dir.join("store"));
+        let version: NvimVersion = "v0.9.5".parse().unwrap();
+        let mut files = store.add_prefix_install(&version, &prefix).unwrap();
+        files.sort();
+
+        assert_eq!(
+            files,
+            [
+                "bin/nvim",
+                "lib/nvim/parser/c.so",
+                "share/nvim/runtime/filetype.lua"
+            ]
+            .map(PathBuf::from)
+        );
+        assert!(store
+            .version_dir(&version)
+            .join("share/nvim/runtime/filetype.lua")
+            .is_file());
+    }
+
+    #[test]
+    fn extracts_below_the_top_level_directory() {
+        let dir = temp_dir("extract");
+        let archive = tarball(
+            &dir,
+            &[
+                Entry::File("nvim-linux64/bin/nvim", b"binary"),
+                Entry::File("nvim-linux64/share/nvim/runtime/filetype.lua", b"lua"),
+                Entry::Symlink("nvim-linux64/share/nvim/rt", "runtime"),
+            ],
+        );
+        let dest = dir.join("dest");
+
+        let mut files = extract_tarball(&archive, &dest).unwrap();
+        files.sort();
+
+        assert_eq!(
+            files,
+            [
+                "bin/nvim",
+                "share/nvim/rt",
+                "share/nvim/runtime/filetype.lua"
+            ]
+            .map(PathBuf::from)
+        );
+        assert_eq!(std::fs::read(dest.join("bin/nvim")).unwrap(), b"binary");
+    }
+
+    #[test]
+    fn refuses_to_write_through_a_symlink() {
+        let dir = temp_dir("symlink-escape");
+        let outside = dir.join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        let archive = tarball(
+            &dir,
+            &[
+                Entry::Symlink("nvim-linux64/a", outside.to_str().unwrap()),
+                Entry::File("nvim-linux64/a/passwd", b"owned"),
+            ],
+        );
+
+        assert!(extract_tarball(&archive, &dir.join("dest")).is_err());
+        assert!(!outside.join("passwd").exists());
+    }
+
+    #[test]
+    fn refuses_relative_symlinks_leaving_the_archive() {
+        let dir = temp_dir("relative-escape");
+        let archive = tarball(
+            &dir,
+            &[
+                Entry::Symlink("nvim-linux64/share/a", "../../outside"),
+                Entry::File("nvim-linux64/share/a/passwd", b"owned"),
+            ],
+        );
+
+        assert!(extract_tarball(&archive, &dir.join("dest")).is_err());
+        assert!(!dir.join("outside").exists());
+    }
+
+    #[test]
+    fn refuses_hard_links() {
+        let dir = temp_dir("hard-link");
+        let victim = dir.join("victim");
+        std::fs::write(&victim, b"original").unwrap();
+        let archive = tarball(
+            &dir,
+            &[
+                Entry::HardLink("nvim-linux64/a", victim.to_str().unwrap()),
+                Entry::File("nvim-linux64/a", b"owned"),
+            ],
+        );
+
+        assert!(extract_tarball(&archive, &dir.join("dest")).is_err());
+        assert_eq!(std::fs::read(&victim).unwrap(), b"original");
+    }
+}
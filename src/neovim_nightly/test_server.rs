@@ -0,0 +1,65 @@
+//! A stand-in HTTP server for tests that talk to GitHub or download files.
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serves every connection on a local port with the bytes `respond` returns
+/// for its request head, then closes it. Returns the server's base URL.
+///
+/// Writing fewer body bytes than the `Content-Length` promises stands in for
+/// a connection dropped mid-transfer.
+pub async fn serve<F>(respond: F) -> String
+where
+    F: Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let respond = Arc::new(respond);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buffer = [0; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => head.extend_from_slice(&buffer[..read]),
+                    }
+                }
+                let response = respond(&String::from_utf8_lossy(&head));
+                let _ = stream.write_all(&response).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    format!("http://{}", address)
+}
+
+/// A response with `status` (e.g. `200 OK`), extra `headers` and a
+/// `Content-Length` matching `body`.
+pub fn response(status: &str, headers: &[(&str, String)], body: &[u8]) -> Vec<u8> {
+    response_with_length(status, headers, body, body.len())
+}
+
+/// A response that promises `length` body bytes but sends only `body`.
+pub fn response_with_length(
+    status: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+    length: usize,
+) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\n", status, length);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("Connection: close\r\n\r\n");
+
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
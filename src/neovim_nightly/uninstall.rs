@@ -0,0 +1,192 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::{
+    config::Config,
+    elevate::Elevator,
+    history::{self, HistoryAction},
+    paths,
+    plan::{Plan, Step},
+    store::VersionStore,
+    Result,
+};
+
+/// Neovim's own directories, which hold the user's config and plugins rather
+/// than anything neviraller installed, so removing them is opt-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NvimDir {
+    Config,
+    Data,
+    State,
+    Cache,
+}
+
+impl NvimDir {
+    pub const ALL: [NvimDir; 4] = [
+        NvimDir::Config,
+        NvimDir::Data,
+        NvimDir::State,
+        NvimDir::Cache,
+    ];
+
+    pub fn path(&self) -> PathBuf {
+        match self {
+            NvimDir::Config => paths::nvim_config_dir(),
+            NvimDir::Data => paths::nvim_data_dir(),
+            NvimDir::State => paths::nvim_state_dir(),
+            NvimDir::Cache => paths::nvim_cache_dir(),
+        }
+    }
+}
+
+impl fmt::Display for NvimDir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            NvimDir::Config => "config",
+            NvimDir::Data => "data",
+            NvimDir::State => "state",
+            NvimDir::Cache => "cache",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Everything an uninstall would remove, listed before anything is touched.
+#[derive(Debug, Clone, Default)]
+pub struct UninstallPlan {
+    /// Symlinks into the store, e.g. `<prefix>/bin/nvim`, each with what
+    /// removes it, or why nothing can.
+    pub links: Vec<(PathBuf, std::result::Result<Elevator, String>)>,
+    /// Installed versions, each with the files its manifest lists.
+    pub versions: Vec<(PathBuf, usize)>,
+    /// neviraller's data and cache directories.
+    pub dirs: Vec<PathBuf>,
+    pub nvim_dirs: Vec<PathBuf>,
+}
+
+impl UninstallPlan {
+    pub fn new(config: &Config, nvim_dirs: &[NvimDir]) -> Result<UninstallPlan> {
+        let store = VersionStore::new();
+
+        // Installs made before links were recorded still have the default
+        // prefix link.
+        let mut candidates = store.links()?;
+        let prefix_link = config.install_prefix().join("bin").join("nvim");
+        if !candidates.contains(&prefix_link) {
+            candidates.push(prefix_link);
+        }
+        let links = candidates
+            .into_iter()
+            .filter(|link| points_into_store(&store, link))
+            .map(|link| {
+                let dir = link.parent().unwrap_or(Path::new("/"));
+                let elevator = Elevator::for_dir(dir, config.elevator).map_err(|e| e.to_string());
+                (link, elevator)
+            })
+            .collect();
+
+        let versions = store
+            .list()?
+            .into_iter()
+            .map(|installed| {
+                let files = store
+                    .manifest(&installed.version)
+                    .map_or(0, |manifest| manifest.files.len());
+                (installed.path, files)
+            })
+            .collect();
+
+        let dirs = [store.root().to_path_buf(), paths::cache_dir()]
+            .into_iter()
+            .filter(|dir| dir.exists())
+            .collect();
+
+        let nvim_dirs = nvim_dirs
+            .iter()
+            .map(NvimDir::path)
+            .filter(|dir| dir.exists())
+            .collect();
+
+        Ok(UninstallPlan {
+            links,
+            versions,
+            dirs,
+            nvim_dirs,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty() && self.dirs.is_empty() && self.nvim_dirs.is_empty()
+    }
+
+    /// The removals as a plan, for showing before running it.
+    pub fn plan(&self) -> Plan {
+        let mut plan = Plan::new("Uninstall Neovim".to_string());
+        for (link, elevator) in &self.links {
+            let step = Step::Remove(link.clone());
+            match elevator {
+                Ok(elevator) => plan.push_elevated(step, *elevator),
+                Err(reason) => plan.push(Step::Blocked {
+                    step: Box::new(step),
+                    reason: reason.clone(),
+                }),
+            }
+        }
+        for (dir, files) in &self.versions {
+            plan.push(Step::RemoveVersion {
+                dir: dir.clone(),
+                files: *files,
+            });
+        }
+        for dir in self.dirs.iter().chain(&self.nvim_dirs) {
+            plan.push(Step::Remove(dir.clone()));
+        }
+        plan
+    }
+
+    /// Removes everything in the plan. Links in directories the user can't
+    /// write to are removed through their elevator; when one has none, nothing
+    /// is removed.
+    pub fn run(&self) -> Result<()> {
+        let previous = VersionStore::new().current().ok().flatten();
+        let result = self.remove_all();
+        history::record_result(HistoryAction::Uninstall, previous, None, &result);
+        result
+    }
+
+    fn remove_all(&self) -> Result<()> {
+        let mut links = Vec::new();
+        for (link, elevator) in &self.links {
+            match elevator {
+                Ok(elevator) => links.push((link, elevator)),
+                Err(reason) => {
+                    return Err(format!("Can't remove {}: {}", link.display(), reason).into())
+                }
+            }
+        }
+
+        for (link, elevator) in links {
+            if elevator.is_elevated() {
+                elevator.run(elevator.command("rm").arg("-f").arg(link))?;
+            } else {
+                std::fs::remove_file(link)?;
+            }
+        }
+
+        for dir in self.dirs.iter().chain(&self.nvim_dirs) {
+            match std::fs::remove_dir_all(dir) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to remove {}: {}", dir.display(), e).into()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Only links that still resolve into the store are ours to remove; anything
+/// else at that path was put there by someone else.
+fn points_into_store(store: &VersionStore, link: &Path) -> bool {
+    std::fs::read_link(link).is_ok_and(|target| target.starts_with(store.root()))
+}
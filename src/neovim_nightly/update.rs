@@ -1,36 +1,881 @@
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub async fn update_neovim() -> Result<(), Box<dyn std::error::Error>> {
-    let nvim_url = "https://github.com/neovim/neovim/releases/download/nightly/nvim.appimage";
-    let response = reqwest::get(nvim_url).await?;
-
-    if response.status().is_success() {
-        let path = "/tmp/nvim.appimage";
-        let mut file = tokio::fs::File::create(path).await?;
-        let content = response.bytes().await?;
-        tokio::io::copy(&mut &content[..], &mut file).await?;
-
-        Command::new("sudo")
-            .arg("chmod")
-            .arg("+x")
-            .arg(path)
-            .status()
-            .expect("Failed to execute chmod");
-
-        Command::new("sudo")
-            .arg("mv")
-            .arg(path)
-            .arg("/usr/local/bin/nvim")
-            .status()
-            .expect("Failed to move file to /usr/local/bin");
-
-        println!("Neovim Nightly has been updated successfully!");
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{
+    atomic,
+    channel::Channel,
+    checksum,
+    config::{Config, ElevatorKind, InstallFormat},
+    download::{download, Progress},
+    elevate::Elevator,
+    history::{HistoryAction, PendingInstall},
+    lock, paths,
+    plan::{Plan, Step},
+    platform::{self, Platform},
+    release::{Asset, Release, ReleaseSource},
+    source_build::SourceBuild,
+    store::{self, VersionStore, PREFIX_DIRS},
+    version::NvimVersion,
+    Result,
+};
+
+pub struct InstallOutcome {
+    pub version: NvimVersion,
+    pub replaced: Option<NvimVersion>,
+    pub target: PathBuf,
+    /// sha256 of the installed artifact; `None` for source builds.
+    pub sha256: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// The release asset an install from a channel uses, settled before anything
+/// is downloaded so that confirming a plan installs exactly what it showed,
+/// even when the channel has been re-tagged since.
+#[derive(Debug, Clone)]
+pub struct ReleaseChoice {
+    pub channel: Channel,
+    pub release: Release,
+    pub platform: Platform,
+    pub asset: Asset,
+    pub format: InstallFormat,
+    pub version: NvimVersion,
+    pub sha256: String,
+}
+
+/// Looks up the current release of `channel` and the asset for this platform.
+pub async fn choose_release(channel: &Channel, config: &Config) -> Result<ReleaseChoice> {
+    let release = ReleaseSource::new()?.release(channel).await?;
+    let platform = Platform::detect();
+    let (asset, format) = select_asset(&release, &platform, config)?;
+    let asset = asset.clone();
+    let version = release
+        .version()
+        .ok_or_else(|| format!("Couldn't determine the version of {}", release.tag_name))?;
+    let sha256 = checksum::expected_sha256(&release, &asset).await?;
+
+    Ok(ReleaseChoice {
+        channel: channel.clone(),
+        release,
+        platform,
+        asset,
+        format,
+        version,
+        sha256,
+    })
+}
+
+/// A release asset for this platform, downloaded into the cache and verified
+/// against its published checksum.
+pub struct DownloadedArtifact {
+    pub release: Release,
+    pub platform: Platform,
+    pub format: InstallFormat,
+    pub version: NvimVersion,
+    pub sha256: String,
+    pub path: PathBuf,
+}
+
+pub async fn download_artifact(
+    choice: &ReleaseChoice,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<DownloadedArtifact> {
+    let path = download_path(&choice.release, &choice.asset);
+    download(&choice.asset.browser_download_url, &path, progress)
+        .await
+        .map_err(|e| format!("Failed to download Neovim {}. Error: {}", choice.channel, e))?;
+
+    if let Err(e) = checksum::verify_file(&path, &choice.sha256).await {
+        tokio::fs::remove_file(&path).await?;
+        return Err(e);
+    }
+
+    Ok(DownloadedArtifact {
+        release: choice.release.clone(),
+        platform: choice.platform.clone(),
+        format: choice.format,
+        version: choice.version.clone(),
+        sha256: choice.sha256.clone(),
+        path,
+    })
+}
+
+fn download_path(release: &Release, asset: &Asset) -> PathBuf {
+    paths::cache_dir()
+        .join("downloads")
+        .join(&release.tag_name)
+        .join(&asset.name)
+}
+
+/// The steps `update_neovim` would take with `choice`. Nothing is downloaded
+/// or changed.
+pub fn plan_update(choice: &ReleaseChoice, config: &Config) -> Result<Plan> {
+    let ReleaseChoice {
+        channel,
+        release,
+        asset,
+        format,
+        version,
+        sha256,
+        ..
+    } = choice;
+    let path = download_path(release, asset);
+
+    let store = VersionStore::new();
+    let mut plan = Plan::new(format!("Install Neovim {} ({})", version, channel));
+    plan.push(Step::Download {
+        url: asset.browser_download_url.clone(),
+        dest: path.clone(),
+        size: Some(asset.size),
+    });
+    plan.push(Step::Verify {
+        file: path.clone(),
+        sha256: Some(sha256.clone()),
+    });
+    plan_adopt(&mut plan, &store, &prefix_binary(config));
+    plan_store(
+        &mut plan,
+        &store.planned_version_dir(Some(version)),
+        &path,
+        *format,
+        config,
+    );
+    plan_activate(&mut plan, &store, Some(version), config)?;
+
+    Ok(plan)
+}
+
+/// Installs the asset in `choice`, as planned by `plan_update`.
+pub async fn update_neovim(
+    choice: &ReleaseChoice,
+    config: &Config,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<InstallOutcome> {
+    let pending = PendingInstall::new(HistoryAction::Install, current_version());
+    let mut result = install_release(choice, config, progress).await;
+    pending.finish(&mut result);
+    result
+}
+
+async fn install_release(
+    choice: &ReleaseChoice,
+    config: &Config,
+    progress: Option<&UnboundedSender<Progress>>,
+) -> Result<InstallOutcome> {
+    let DownloadedArtifact {
+        platform,
+        format,
+        version,
+        sha256,
+        path,
+        ..
+    } = download_artifact(choice, progress).await?;
+
+    let store = VersionStore::new();
+    adopt_existing_binary(&store, &prefix_binary(config))?;
+
+    let mut warnings: Vec<String> = Vec::new();
+    let preferred = config.install_format();
+    if format != preferred {
+        warnings.push(format!(
+            "No {} is published for {}, installed the {} instead",
+            preferred.as_str(),
+            platform,
+            format.as_str()
+        ));
+    } else if format == InstallFormat::Tarball && config.format == InstallFormat::Auto {
+        warnings
+            .push("FUSE is not available, installed the tarball instead of the AppImage".into());
+    }
+
+    store_artifact(&store, &version, &path, format, config, &mut warnings).await?;
+
+    activate(&store, version, config, Some(sha256), warnings)
+}
+
+/// How `install_file` checks a pre-downloaded artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChecksum {
+    /// The `<file>.sha256sum` or `shasum.txt` next to it; the install fails
+    /// when there is neither.
+    Sidecar,
+    /// A SHA-256 the user got elsewhere, hex-encoded.
+    Sha256(String),
+    /// Installs it without checking.
+    Unverified,
+}
+
+impl FileChecksum {
+    /// The SHA-256 `artifact` must have, `None` when it isn't checked.
+    async fn expected_sha256(&self, artifact: &Path) -> Result<Option<String>> {
+        match self {
+            FileChecksum::Sidecar => match checksum::local_sha256(artifact).await? {
+                Some(sha256) => Ok(Some(sha256)),
+                None => Err(format!(
+                    "No {}.sha256sum or shasum.txt next to {}; give its SHA-256 or install it unverified",
+                    artifact.file_name().unwrap_or_default().to_string_lossy(),
+                    artifact.display()
+                )
+                .into()),
+            },
+            FileChecksum::Sha256(sha256) => checksum::parse_sha256(sha256)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid SHA-256 {:?}", sha256).into()),
+            FileChecksum::Unverified => Ok(None),
+        }
+    }
+}
+
+/// Installs a pre-downloaded AppImage or tarball after checking it as
+/// `verify` says. Its version is read from the binary itself since there is
+/// no release to ask.
+pub async fn install_file(
+    artifact: &Path,
+    verify: &FileChecksum,
+    config: &Config,
+) -> Result<InstallOutcome> {
+    let pending = PendingInstall::new(HistoryAction::InstallFile, current_version());
+    let mut result = install_local(artifact, verify, config).await;
+    pending.finish(&mut result);
+    result
+}
+
+async fn install_local(
+    artifact: &Path,
+    verify: &FileChecksum,
+    config: &Config,
+) -> Result<InstallOutcome> {
+    let (name, format) = local_format(artifact)?;
+
+    let mut warnings = Vec::new();
+    let expected_sha256 = verify.expected_sha256(artifact).await?;
+    if expected_sha256.is_none() {
+        warnings.push(format!("{} was installed unverified", artifact.display()));
+    }
+
+    // Work on a copy so the user's file is never modified or removed.
+    let path = local_copy_path(name);
+    tokio::fs::create_dir_all(path.parent().unwrap_or(Path::new("/"))).await?;
+    tokio::fs::copy(artifact, &path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", artifact.display(), e))?;
+
+    let sha256 = match expected_sha256 {
+        Some(expected_sha256) => {
+            if let Err(e) = checksum::verify_file(&path, &expected_sha256).await {
+                tokio::fs::remove_file(&path).await?;
+                return Err(e);
+            }
+            expected_sha256
+        }
+        None => checksum::sha256_file(&path).await?,
+    };
+
+    let version = match probe_version(&path, format).await {
+        Ok(version) => version,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&path).await;
+            return Err(e);
+        }
+    };
+
+    let store = VersionStore::new();
+    adopt_existing_binary(&store, &prefix_binary(config))?;
+    store_artifact(&store, &version, &path, format, config, &mut warnings).await?;
+
+    activate(&store, version, config, Some(sha256), warnings)
+}
+
+/// The steps `install_file` would take. The version is only known once the
+/// artifact has been run, so the store paths show a placeholder for it.
+pub async fn plan_install_file(
+    artifact: &Path,
+    verify: &FileChecksum,
+    config: &Config,
+) -> Result<Plan> {
+    let (name, format) = local_format(artifact)?;
+    let expected_sha256 = verify.expected_sha256(artifact).await?;
+    let path = local_copy_path(name);
+
+    let store = VersionStore::new();
+    let mut plan = Plan::new(format!("Install Neovim from {}", artifact.display()));
+    plan.push(Step::Copy {
+        from: artifact.to_path_buf(),
+        to: path.clone(),
+    });
+    if expected_sha256.is_some() {
+        plan.push(Step::Verify {
+            file: path.clone(),
+            sha256: expected_sha256,
+        });
+    }
+    plan_adopt(&mut plan, &store, &prefix_binary(config));
+    plan_store(
+        &mut plan,
+        &store.planned_version_dir(None),
+        &path,
+        format,
+        config,
+    );
+    plan_activate(&mut plan, &store, None, config)?;
+
+    Ok(plan)
+}
+
+fn local_format(artifact: &Path) -> Result<(&str, InstallFormat)> {
+    let name = artifact
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid artifact path {}", artifact.display()))?;
+    let format = artifact_format(name)
+        .ok_or_else(|| format!("{} is neither an AppImage nor a .tar.gz tarball", name))?;
+    Ok((name, format))
+}
+
+fn local_copy_path(name: &str) -> PathBuf {
+    paths::cache_dir()
+        .join("downloads")
+        .join("local")
+        .join(name)
+}
+
+pub fn artifact_format(file_name: &str) -> Option<InstallFormat> {
+    let lower = file_name.to_lowercase();
+    if lower.ends_with(".tar.gz") {
+        Some(InstallFormat::Tarball)
+    } else if lower.ends_with(".appimage") {
+        Some(InstallFormat::AppImage)
     } else {
-        eprintln!(
-            "Failed to download Neovim Nightly. Error: {}",
-            response.status()
-        );
+        None
+    }
+}
+
+/// Reads the version of an artifact by running it, unpacking it into a
+/// scratch directory first when it is a tarball or an AppImage that can't be
+/// mounted.
+async fn probe_version(path: &Path, format: InstallFormat) -> Result<NvimVersion> {
+    if format != InstallFormat::Tarball {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+        if let Ok(version) = atomic::validate_binary(path) {
+            return Ok(version);
+        }
+    }
+
+    let (artifact, scratch) = (
+        path.to_path_buf(),
+        paths::cache_dir()
+            .join("probe")
+            .join(std::process::id().to_string()),
+    );
+    tokio::task::spawn_blocking(move || {
+        let _ = std::fs::remove_dir_all(&scratch);
+        std::fs::create_dir_all(&scratch)?;
+        let result = (|| match format {
+            InstallFormat::Tarball => {
+                store::extract_tarball(&artifact, &scratch)?;
+                atomic::validate_binary(&scratch.join("bin").join("nvim"))
+            }
+            InstallFormat::AppImage | InstallFormat::Auto => {
+                atomic::run(
+                    Command::new(&artifact)
+                        .arg("--appimage-extract")
+                        .current_dir(&scratch),
+                )?;
+                atomic::validate_binary(&scratch.join("squashfs-root/usr/bin/nvim"))
+            }
+        })();
+        let _ = std::fs::remove_dir_all(&scratch);
+        result
+    })
+    .await?
+}
+
+/// Moves a verified artifact into the store in the given format and removes
+/// it from the download cache.
+async fn store_artifact(
+    store: &VersionStore,
+    version: &NvimVersion,
+    path: &Path,
+    format: InstallFormat,
+    config: &Config,
+    warnings: &mut Vec<String>,
+) -> Result<()> {
+    match format {
+        InstallFormat::Tarball => {
+            let (archive, tarball_version) = (path.to_path_buf(), version.clone());
+            lock::spawn_blocking(move || {
+                VersionStore::new().add_tarball(&tarball_version, &archive)
+            })
+            .await??;
+        }
+        InstallFormat::AppImage | InstallFormat::Auto => {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+            let needs_fuse = !config.appimage_extract && platform::appimage_needs_fuse(path);
+            if needs_fuse {
+                warnings.push("FUSE is not available, extracted the AppImage instead".into());
+            }
+
+            if config.appimage_extract || needs_fuse {
+                let (appimage, appimage_version) = (path.to_path_buf(), version.clone());
+                lock::spawn_blocking(move || {
+                    VersionStore::new().add_extracted_appimage(&appimage_version, &appimage)
+                })
+                .await??;
+            } else {
+                store.add_binary(version, path)?;
+            }
+        }
+    }
+    let _ = tokio::fs::remove_file(path).await;
+
+    Ok(())
+}
+
+/// Adds the steps `store_artifact` takes to `plan`. Whether an AppImage has
+/// to be extracted is decided from `fuse_available` alone, since the artifact
+/// isn't there to be run yet.
+fn plan_store(
+    plan: &mut Plan,
+    version_dir: &Path,
+    path: &Path,
+    format: InstallFormat,
+    config: &Config,
+) {
+    let staged = atomic::staged_path(version_dir);
+    match format {
+        InstallFormat::Tarball => {
+            plan.push(Step::Extract {
+                archive: path.to_path_buf(),
+                dest: staged.clone(),
+            });
+            plan.push(Step::Move {
+                from: staged,
+                to: version_dir.to_path_buf(),
+            });
+        }
+        InstallFormat::AppImage | InstallFormat::Auto => {
+            plan.push(Step::Chmod {
+                path: path.to_path_buf(),
+                mode: 0o755,
+            });
+            if config.appimage_extract || !platform::fuse_available() {
+                plan.push(Step::Extract {
+                    archive: path.to_path_buf(),
+                    dest: staged.join("squashfs-root"),
+                });
+                plan.push(Step::Link {
+                    link: staged.join("bin").join("nvim"),
+                    target: Path::new("../squashfs-root/usr/bin/nvim").to_path_buf(),
+                });
+                plan.push(Step::Move {
+                    from: staged,
+                    to: version_dir.to_path_buf(),
+                });
+            } else {
+                plan_copy_binary(plan, path, &version_dir.join("bin").join("nvim"));
+            }
+        }
+    }
+    plan.push(Step::Remove(path.to_path_buf()));
+}
+
+/// The steps of `atomic::replace_file`.
+fn plan_copy_binary(plan: &mut Plan, source: &Path, target: &Path) {
+    let staged = atomic::staged_path(target);
+    plan.push(Step::Copy {
+        from: source.to_path_buf(),
+        to: staged.clone(),
+    });
+    plan.push(Step::Chmod {
+        path: staged.clone(),
+        mode: 0o755,
+    });
+    plan.push(Step::Move {
+        from: staged,
+        to: target.to_path_buf(),
+    });
+}
+
+/// The steps `build_neovim` would take. The version is only known once the
+/// build has finished, so the store paths show a placeholder for it.
+pub fn plan_build(channel: &Channel, config: &Config) -> Result<Plan> {
+    let build = SourceBuild::new(channel.tag(), &config.build);
+    let store = VersionStore::new();
+    let mut plan = Plan::new(format!("Build Neovim from source ({})", build.git_ref));
+    plan_adopt(&mut plan, &store, &prefix_binary(config));
+    build.plan(&mut plan, &store.planned_version_dir(None));
+    plan_activate(&mut plan, &store, None, config)?;
+    Ok(plan)
+}
+
+/// Builds the source of `channel`, or of the configured git ref, and installs
+/// it like a downloaded release. Build output is streamed to `log`.
+pub async fn build_neovim(
+    channel: &Channel,
+    config: &Config,
+    progress: Option<&UnboundedSender<Progress>>,
+    log: &UnboundedSender<String>,
+) -> Result<InstallOutcome> {
+    let pending = PendingInstall::new(HistoryAction::Build, current_version());
+    let mut result = build_source(channel, config, progress, log).await;
+    pending.finish(&mut result);
+    result
+}
+
+async fn build_source(
+    channel: &Channel,
+    config: &Config,
+    progress: Option<&UnboundedSender<Progress>>,
+    log: &UnboundedSender<String>,
+) -> Result<InstallOutcome> {
+    let store = VersionStore::new();
+    adopt_existing_binary(&store, &prefix_binary(config))?;
+
+    let version = SourceBuild::new(channel.tag(), &config.build)
+        .install(progress, log)
+        .await?;
+
+    activate(&store, version, config, None, Vec::new())
+}
+
+fn prefix_binary(config: &Config) -> PathBuf {
+    config.install_prefix().join("bin").join("nvim")
+}
+
+/// The active version before an install, for the history.
+fn current_version() -> Option<NvimVersion> {
+    VersionStore::new().current().ok().flatten()
+}
+
+/// Makes the freshly stored `version` current and links it into the install
+/// prefix.
+fn activate(
+    store: &VersionStore,
+    version: NvimVersion,
+    config: &Config,
+    sha256: Option<String>,
+    mut warnings: Vec<String>,
+) -> Result<InstallOutcome> {
+    let target = prefix_binary(config);
+    let bin_dir = target.parent().unwrap_or(Path::new("/")).to_path_buf();
+
+    // Whatever linking needs is resolved before switching, and the switch is
+    // undone if linking fails anyway, so the store never points at a version
+    // the prefix doesn't run.
+    let current = store.current_binary();
+    let elevator = link_elevator(&current, &bin_dir, &target, config.elevator)?;
+    let replaced = store.switch(&version)?;
+    if let Err(e) = link_into_prefix(store, &current, &bin_dir, &target, elevator) {
+        if let Some(replaced) = &replaced {
+            let _ = store.switch(replaced);
+        }
+        return Err(e);
+    }
+
+    if !paths::is_on_path(&bin_dir) {
+        warnings.push(format!(
+            "{} is not on your PATH; add it to run the installed nvim",
+            bin_dir.display()
+        ));
     }
 
+    Ok(InstallOutcome {
+        version,
+        replaced,
+        target,
+        sha256,
+        warnings,
+    })
+}
+
+/// Adds the steps `activate` takes to `plan`.
+fn plan_activate(
+    plan: &mut Plan,
+    store: &VersionStore,
+    version: Option<&NvimVersion>,
+    config: &Config,
+) -> Result<()> {
+    store.plan_switch(plan, version);
+
+    let target = prefix_binary(config);
+    let current = store.current_binary();
+    if std::fs::read_link(&target).is_ok_and(|link| link == current) {
+        return Ok(());
+    }
+
+    let bin_dir = target.parent().unwrap_or(Path::new("/"));
+    let elevator = Elevator::for_dir(bin_dir, config.elevator)?;
+    if !bin_dir.exists() {
+        plan.push_elevated(Step::CreateDir(bin_dir.to_path_buf()), elevator);
+    }
+    let staged = atomic::staged_path(&target);
+    plan.push_elevated(
+        Step::Link {
+            link: staged.clone(),
+            target: current,
+        },
+        elevator,
+    );
+    plan.push_elevated(
+        Step::Move {
+            from: staged,
+            to: target,
+        },
+        elevator,
+    );
     Ok(())
 }
+
+/// Picks the release asset built for `platform` in the configured format. When
+/// the format is chosen automatically and the release lacks it for this
+/// platform, the other format is used instead.
+fn select_asset<'a>(
+    release: &'a Release,
+    platform: &Platform,
+    config: &Config,
+) -> Result<(&'a Asset, InstallFormat)> {
+    let preferred = config.install_format();
+    let mut formats = vec![preferred];
+    if config.format == InstallFormat::Auto {
+        formats.push(match preferred {
+            InstallFormat::Tarball => InstallFormat::AppImage,
+            _ => InstallFormat::Tarball,
+        });
+    }
+
+    formats
+        .into_iter()
+        .find_map(|format| {
+            platform
+                .asset_names(format)
+                .iter()
+                .find_map(|name| release.asset(name))
+                .map(|asset| (asset, format))
+        })
+        .ok_or_else(|| {
+            format!(
+                "Release {} has no prebuilt Neovim for {}; use \"Build from source\" instead",
+                release.tag_name, platform
+            )
+            .into()
+        })
+}
+
+/// Copies an `nvim` at `target` that wasn't installed through the store into
+/// it, together with the runtime and parsers installed next to it, and makes
+/// it the active version when the store has none yet, so the install that
+/// replaces it can be rolled back.
+fn adopt_existing_binary(store: &VersionStore, target: &Path) -> Result<()> {
+    let is_file = std::fs::symlink_metadata(target).is_ok_and(|metadata| metadata.is_file());
+    if !is_file {
+        return Ok(());
+    }
+
+    let Ok(version) = atomic::validate_binary(target) else {
+        return Ok(());
+    };
+
+    if !store.contains(&version) {
+        store.add_prefix_install(&version, prefix_of(target))?;
+    }
+    if store.current()?.is_none() {
+        store.switch(&version)?;
+    }
+
+    Ok(())
+}
+
+/// The prefix `<prefix>/bin/nvim` was installed into.
+fn prefix_of(binary: &Path) -> &Path {
+    binary
+        .parent()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new("/"))
+}
+
+/// Adds the steps `adopt_existing_binary` takes to `plan`. The existing `nvim`
+/// isn't run while planning, so its version, and with it whether the store
+/// already holds it, stays open.
+fn plan_adopt(plan: &mut Plan, store: &VersionStore, target: &Path) {
+    let is_file = std::fs::symlink_metadata(target).is_ok_and(|metadata| metadata.is_file());
+    if !is_file {
+        return;
+    }
+
+    let version_dir = store.planned_version_dir(None);
+    let staged = atomic::staged_path(&version_dir);
+    let prefix = prefix_of(target);
+    plan.push(Step::Run(format!("{} --version", target.display())));
+    plan.push(Step::Copy {
+        from: target.to_path_buf(),
+        to: staged.join("bin").join("nvim"),
+    });
+    for dir in PREFIX_DIRS {
+        if prefix.join(dir).is_dir() {
+            plan.push(Step::Copy {
+                from: prefix.join(dir),
+                to: staged.join(dir),
+            });
+        }
+    }
+    plan.push(Step::Move {
+        from: staged,
+        to: version_dir,
+    });
+    if store.current().ok().flatten().is_none() {
+        store.plan_switch(plan, None);
+    }
+}
+
+/// The elevator `link_into_prefix` needs, `None` when `target` already links
+/// to `current`.
+fn link_elevator(
+    current: &Path,
+    bin_dir: &Path,
+    target: &Path,
+    preference: ElevatorKind,
+) -> Result<Option<Elevator>> {
+    if std::fs::read_link(target).is_ok_and(|link| link == current) {
+        return Ok(None);
+    }
+    Elevator::for_dir(bin_dir, preference).map(Some)
+}
+
+/// Points `<prefix>/bin/nvim` at the store's `current` binary. Once the link
+/// exists, switching versions only touches the store.
+fn link_into_prefix(
+    store: &VersionStore,
+    current: &Path,
+    bin_dir: &Path,
+    target: &Path,
+    elevator: Option<Elevator>,
+) -> Result<()> {
+    let Some(elevator) = elevator else {
+        return store.record_link(target);
+    };
+
+    if elevator.is_elevated() {
+        elevator.run(elevator.command("mkdir").arg("-p").arg(bin_dir))?;
+    } else {
+        std::fs::create_dir_all(bin_dir)?;
+    }
+
+    atomic::replace_symlink(current, target, elevator)?;
+    store.record_link(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neovim_nightly::platform::{Arch, Libc};
+
+    fn release(assets: &[&str]) -> Release {
+        Release {
+            tag_name: "v0.11.0".to_string(),
+            body: None,
+            assets: assets
+                .iter()
+                .map(|name| Asset {
+                    name: name.to_string(),
+                    size: 1,
+                    browser_download_url: format!("https://example.invalid/{}", name),
+                    digest: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn platform(arch: Arch, libc: Libc) -> Platform {
+        Platform { arch, libc }
+    }
+
+    fn config(format: InstallFormat) -> Config {
+        Config {
+            format,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn selects_the_asset_for_the_platform_and_format() {
+        let current = [
+            "nvim-linux-x86_64.appimage",
+            "nvim-linux-x86_64.tar.gz",
+            "nvim-linux-arm64.appimage",
+            "nvim-linux-arm64.tar.gz",
+        ];
+        let legacy = ["nvim.appimage", "nvim-linux64.tar.gz"];
+        let cases = [
+            (
+                &current[..],
+                Arch::X86_64,
+                InstallFormat::Tarball,
+                "nvim-linux-x86_64.tar.gz",
+            ),
+            (
+                &current[..],
+                Arch::X86_64,
+                InstallFormat::AppImage,
+                "nvim-linux-x86_64.appimage",
+            ),
+            (
+                &current[..],
+                Arch::Aarch64,
+                InstallFormat::Tarball,
+                "nvim-linux-arm64.tar.gz",
+            ),
+            (
+                &current[..],
+                Arch::Aarch64,
+                InstallFormat::AppImage,
+                "nvim-linux-arm64.appimage",
+            ),
+            (
+                &legacy[..],
+                Arch::X86_64,
+                InstallFormat::Tarball,
+                "nvim-linux64.tar.gz",
+            ),
+            (
+                &legacy[..],
+                Arch::X86_64,
+                InstallFormat::AppImage,
+                "nvim.appimage",
+            ),
+        ];
+
+        for (assets, arch, format, expected) in cases {
+            let release = release(assets);
+            let (asset, chosen) =
+                select_asset(&release, &platform(arch, Libc::Glibc), &config(format)).unwrap();
+            assert_eq!((asset.name.as_str(), chosen), (expected, format));
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_other_format_only_when_automatic() {
+        let release = release(&["nvim-linux-arm64.tar.gz"]);
+        let arm = platform(Arch::Aarch64, Libc::Glibc);
+
+        let (asset, format) = select_asset(&release, &arm, &config(InstallFormat::Auto)).unwrap();
+        assert_eq!(asset.name, "nvim-linux-arm64.tar.gz");
+        assert_eq!(format, InstallFormat::Tarball);
+
+        assert!(select_asset(&release, &arm, &config(InstallFormat::AppImage)).is_err());
+    }
+
+    #[test]
+    fn has_nothing_for_musl() {
+        let release = release(&["nvim-linux-x86_64.appimage", "nvim-linux-x86_64.tar.gz"]);
+        let error = select_asset(
+            &release,
+            &platform(Arch::X86_64, Libc::Musl),
+            &config(InstallFormat::Auto),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("Build from source"), "{}", error);
+    }
+}
@@ -1,9 +1,21 @@
-pub async fn check_neovim_version(
-    new_version_line: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let output = std::process::Command::new("nvim")
-        .arg("--version")
-        .output()?;
+use std::cmp::Ordering;
+use std::io::ErrorKind;
+
+use super::{version::NvimVersion, Result};
+
+pub enum UpdateCheck {
+    NotInstalled,
+    UpToDate(NvimVersion),
+    Available(NvimVersion),
+    Downgrade(NvimVersion),
+}
+
+pub fn installed_version() -> Result<Option<NvimVersion>> {
+    let output = match std::process::Command::new("nvim").arg("--version").output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
 
     let current_version_output = std::str::from_utf8(&output.stdout)?;
 
@@ -12,23 +24,17 @@ pub async fn check_neovim_version(
         .next()
         .ok_or("Failed to get current Neovim version")?;
 
-    let current_version = current_version_line
-        .split_whitespace()
-        .nth(1)
-        .ok_or("Failed to parse current Neovim version")?;
-
-    println!("Current installed Neovim version: {}", current_version);
-
-    let new_version = new_version_line.lines().next().ok_or("problem1")?;
+    Ok(Some(current_version_line.parse::<NvimVersion>()?))
+}
 
-    if current_version == new_version {
-        println!("You are already using the latest Neovim version");
-        return Err("Already up to date".into());
-    }
-    println!(
-        "There is a newer version of Neovim Nightly available: {}",
-        new_version
-    );
+pub fn check_neovim_version(new_version: &NvimVersion) -> Result<UpdateCheck> {
+    let Some(current_version) = installed_version()? else {
+        return Ok(UpdateCheck::NotInstalled);
+    };
 
-    Ok(())
+    Ok(match current_version.cmp(new_version) {
+        Ordering::Equal => UpdateCheck::UpToDate(current_version),
+        Ordering::Less => UpdateCheck::Available(current_version),
+        Ordering::Greater => UpdateCheck::Downgrade(current_version),
+    })
 }
@@ -0,0 +1,305 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A Neovim version as printed by `nvim --version` or used in release tags,
+/// e.g. `v0.10.2`, `v0.11.0-dev-1234+gabcdef12` or `v0.5.0-dev+1357-g192f89ea1`.
+#[derive(Debug, Clone)]
+pub struct NvimVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub prerelease: Option<String>,
+    pub build: Option<u64>,
+    pub commit: Option<String>,
+}
+
+impl FromStr for NvimVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid Neovim version: {:?}", s);
+
+        let trimmed = s.trim();
+        let trimmed = trimmed.strip_prefix("NVIM ").unwrap_or(trimmed).trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+
+        let (core, rest) = match trimmed.find(['-', '+']) {
+            Some(index) => trimmed.split_at(index),
+            None => (trimmed, ""),
+        };
+
+        let mut numbers = core.split('.').map(|part| part.parse::<u64>());
+        let major = numbers.next().and_then(Result::ok).ok_or_else(invalid)?;
+        let minor = numbers.next().and_then(Result::ok).ok_or_else(invalid)?;
+        let patch = match numbers.next() {
+            Some(patch) => patch.map_err(|_| invalid())?,
+            None => 0,
+        };
+        if numbers.next().is_some() {
+            return Err(invalid());
+        }
+
+        let mut prerelease = Vec::new();
+        let mut build = None;
+        let mut commit = None;
+        let mut start = 0;
+        for token in rest.split(['-', '+']) {
+            // `+g<hash>` is always a commit, which git abbreviates to as few as
+            // 4 digits; elsewhere only 7 or more are taken as one, so words
+            // such as `gaffe` stay part of the prerelease.
+            let after_plus = start > 0 && rest.as_bytes()[start - 1] == b'+';
+            start += token.len() + 1;
+            if token.is_empty() {
+                continue;
+            }
+            let min_hash_len = if after_plus { 4 } else { 7 };
+            let hash = token.strip_prefix('g').filter(|hash| {
+                hash.len() >= min_hash_len && hash.chars().all(|c| c.is_ascii_hexdigit())
+            });
+
+            if let Some(hash) = hash {
+                commit = Some(hash.to_lowercase());
+            } else if token.chars().all(|c| c.is_ascii_digit()) {
+                build = Some(token.parse::<u64>().map_err(|_| invalid())?);
+            } else if token.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') {
+                prerelease.push(token);
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        Ok(NvimVersion {
+            major,
+            minor,
+            patch,
+            prerelease: (!prerelease.is_empty()).then(|| prerelease.join(".")),
+            build,
+            commit,
+        })
+    }
+}
+
+impl fmt::Display for NvimVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(prerelease) = &self.prerelease {
+            write!(f, "-{}", prerelease)?;
+        }
+        if let Some(build) = self.build {
+            write!(f, "-{}", build)?;
+        }
+        if let Some(commit) = &self.commit {
+            write!(f, "+g{}", commit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Versions compare by number, prerelease and build count. The commit takes no
+/// part: hashes don't order builds, and an abbreviated and a full hash of the
+/// same build must not look like an upgrade.
+impl Ord for NvimVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            // A final release sorts after any prerelease of the same version.
+            .then_with(|| match (&self.prerelease, &other.prerelease) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => prerelease_parts(a).cmp(&prerelease_parts(b)),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialEq for NvimVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NvimVersion {}
+
+/// A run of digits or of other characters in a prerelease; numbers sort before
+/// text, as in semver.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PrereleasePart<'a> {
+    Number(u64),
+    Text(&'a str),
+}
+
+/// Splits a prerelease such as `rc.10` or `rc10` into parts that compare
+/// numbers numerically, so `rc2` sorts before `rc10`.
+fn prerelease_parts(prerelease: &str) -> Vec<PrereleasePart<'_>> {
+    let mut parts = Vec::new();
+    for identifier in prerelease.split('.') {
+        let mut rest = identifier;
+        while let Some(first) = rest.chars().next() {
+            let digits = first.is_ascii_digit();
+            let end = rest
+                .find(|c: char| c.is_ascii_digit() != digits)
+                .unwrap_or(rest.len());
+            let (run, tail) = rest.split_at(end);
+            parts.push(match run.parse::<u64>() {
+                Ok(number) if digits => PrereleasePart::Number(number),
+                _ => PrereleasePart::Text(run),
+            });
+            rest = tail;
+        }
+    }
+    parts
+}
+
+impl PartialOrd for NvimVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for NvimVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for NvimVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> NvimVersion {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_real_world_versions() {
+        let cases = [
+            ("v0.10.2", (0, 10, 2), None, None, None),
+            (
+                "NVIM v0.11.0-dev-1234+gabcdef",
+                (0, 11, 0),
+                Some("dev"),
+                Some(1234),
+                Some("abcdef"),
+            ),
+            (
+                "v0.5.0-dev+1357-g192f89ea1",
+                (0, 5, 0),
+                Some("dev"),
+                Some(1357),
+                Some("192f89ea1"),
+            ),
+            ("0.9.5", (0, 9, 5), None, None, None),
+            ("v0.10", (0, 10, 0), None, None, None),
+            ("v0.10.0-rc.2", (0, 10, 0), Some("rc.2"), None, None),
+            ("v0.10.0-rc2", (0, 10, 0), Some("rc2"), None, None),
+            (
+                "NVIM v0.11.0-dev-1234+gABCDEF1",
+                (0, 11, 0),
+                Some("dev"),
+                Some(1234),
+                Some("abcdef1"),
+            ),
+            (
+                "v0.11.0-dev-1234+gabcdef1234567890abcdef1234567890abcdef12",
+                (0, 11, 0),
+                Some("dev"),
+                Some(1234),
+                Some("abcdef1234567890abcdef1234567890abcdef12"),
+            ),
+            ("v0.11.0-dev", (0, 11, 0), Some("dev"), None, None),
+        ];
+
+        for (input, (major, minor, patch), prerelease, build, commit) in cases {
+            let parsed = version(input);
+            assert_eq!(
+                (parsed.major, parsed.minor, parsed.patch),
+                (major, minor, patch),
+                "{}",
+                input
+            );
+            assert_eq!(parsed.prerelease.as_deref(), prerelease, "{}", input);
+            assert_eq!(parsed.build, build, "{}", input);
+            assert_eq!(parsed.commit.as_deref(), commit, "{}", input);
+        }
+    }
+
+    #[test]
+    fn only_long_hashes_are_commits_after_a_dash() {
+        let parsed = version("v0.11.0-dev-gaffe");
+        assert_eq!(parsed.prerelease.as_deref(), Some("dev.gaffe"));
+        assert_eq!(parsed.commit, None);
+    }
+
+    #[test]
+    fn orders_real_world_versions() {
+        let ordered = [
+            "v0.5.0-dev+1357-g192f89ea1",
+            "0.9.5",
+            "v0.10.2",
+            "NVIM v0.11.0-dev-1234+gabcdef",
+            "v0.11.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(
+                version(pair[0]) < version(pair[1]),
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn orders_prerelease_numbers_numerically() {
+        assert!(version("v0.10.0-rc2") < version("v0.10.0-rc10"));
+        assert!(version("v0.10.0-rc.2") < version("v0.10.0-rc.10"));
+        assert!(version("v0.10.0-rc10") < version("v0.10.0"));
+        assert!(version("v0.10.0-alpha") < version("v0.10.0-beta"));
+    }
+
+    #[test]
+    fn equal_versions_agree_with_their_order() {
+        let equal = [
+            ("v0.10.0-rc.2", "v0.10.0-rc2"),
+            (
+                "v0.11.0-dev-1234+gabcdef1",
+                "v0.11.0-dev-1234+gabcdef12345678",
+            ),
+            ("v0.11.0-dev-1234+gabcdef1", "v0.11.0-dev-1234"),
+            ("v0.10.2", "0.10.2"),
+        ];
+        for (a, b) in equal {
+            assert_eq!(
+                version(a).cmp(&version(b)),
+                Ordering::Equal,
+                "{} = {}",
+                a,
+                b
+            );
+            assert_eq!(version(a), version(b), "{} = {}", a, b);
+        }
+
+        // Hash order means nothing; only the build count orders builds.
+        assert!(version("v0.11.0-dev-1234+gffffff1") < version("v0.11.0-dev-1235+g0000001"));
+        assert!(version("v0.10.0-rc2") != version("v0.10.0-rc3"));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!("nightly".parse::<NvimVersion>().is_err());
+        assert!("v1".parse::<NvimVersion>().is_err());
+        assert!("v0.10.2.1".parse::<NvimVersion>().is_err());
+    }
+}
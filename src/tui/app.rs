@@ -1,8 +1,10 @@
+use std::future::Future;
 use std::time::Duration;
 
 use color_eyre::eyre::Result;
-use crossterm::event::{poll, read, Event};
-use tokio::{sync::mpsc, time};
+use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use futures::StreamExt;
+use tokio::time::{self, MissedTickBehavior};
 
 use ratatui::{
     backend::Backend,
@@ -10,19 +12,58 @@ use ratatui::{
     Terminal,
 };
 
+use crate::neovim_nightly::{
+    channel::Channel,
+    config::Config,
+    elevate::{self, Elevator},
+    history::{self, HistoryAction},
+    lock::{self, InstallLock},
+    plan::Plan,
+    release::{Release, ReleaseSource},
+    store::VersionStore,
+    uninstall::UninstallPlan,
+    update::{
+        build_neovim, choose_release, install_file, plan_build, plan_install_file, plan_update,
+        update_neovim,
+    },
+    ver_compare::{check_neovim_version, installed_version, UpdateCheck},
+    version::NvimVersion,
+};
+
 use super::{
+    channel_picker::ChannelPicker,
+    file_picker::FilePicker,
     keymaps::KeyBindings,
     menu::{self, Menu, MenuAction},
+    password_prompt::PasswordPrompt,
+    plan_dialog::PlanDialog,
+    screens::{Screen, ScreenEvent, ScreenStack},
+    tasks::{InstallSource, PlannedAction, Task, TaskContext, TaskEvent, TaskResult, TaskRunner},
     ui::UI,
+    uninstall_dialog::UninstallDialog,
 };
 
-// use self::neovim_nightly::{
-//     scrap::scrap, update::update_neovim, ver_compare::check_neovim_version,
-// };
+/// How often task progress is drawn while it streams in.
+const RENDER_TICK: Duration = Duration::from_millis(50);
 
 pub struct App {
     menu: Menu,
+    /// Each menu entry's screen, built once; the selected one is shown while
+    /// none is open.
+    previews: Vec<Box<dyn Screen>>,
+    screens: ScreenStack,
     ui: UI,
+    channel: Channel,
+    config: Config,
+    channel_picker: Option<ChannelPicker>,
+    file_picker: Option<FilePicker>,
+    uninstall_dialog: Option<UninstallDialog>,
+    plan_dialog: Option<(PlanDialog, PlannedAction)>,
+    password_prompt: Option<(PasswordPrompt, PlannedAction)>,
+    tasks: TaskRunner,
+    /// Set by a quit while the installation is being changed; quitting again
+    /// confirms it.
+    confirm_quit: bool,
     should_quit: bool,
 }
 
@@ -31,88 +72,611 @@ pub enum Action {
     Select,
     Next,
     Previous,
+    /// Closes the open popup or screen.
+    Back,
     Quit,
     Resize,
+    Delete,
+    Cancel,
+    /// A typed character, for text input. Mapped through the key bindings
+    /// when no input is open.
+    Input(char),
+    Backspace,
 }
 
 impl App {
-    pub fn new(keys: KeyBindings) -> App {
+    pub fn new(keys: KeyBindings, config: Config) -> App {
+        let menu = Menu::new(menu::get_menu_items());
         App {
-            menu: Menu::new(menu::get_menu_items()),
+            previews: menu
+                .sections()
+                .map(|section| section.screen(&config))
+                .collect(),
+            screens: ScreenStack::default(),
+            menu,
             ui: UI::new("Initial update message".to_string(), keys),
+            channel: Channel::default(),
+            config,
+            channel_picker: None,
+            file_picker: None,
+            uninstall_dialog: None,
+            plan_dialog: None,
+            password_prompt: None,
+            tasks: TaskRunner::new(),
+            confirm_quit: false,
             should_quit: false,
         }
     }
 
-    pub async fn run<B: Backend>(
-        &mut self,
-        terminal: &mut Terminal<B>,
-        mut rx: mpsc::UnboundedReceiver<Action>,
-    ) -> Result<()> {
-        loop {
-            terminal.draw(|f| {
-                let size = f.size();
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Length(3),
-                        Constraint::Percentage(50),
-                        Constraint::Percentage(50),
-                        Constraint::Length(3),
-                    ])
-                    .split(size);
-
-                self.ui.render_header(f, chunks[0]);
-                self.ui.render_menu(f, chunks[1], &self.menu);
-                self.ui
-                    .render_additional_info(f, chunks[2], &self.ui.update_message);
-                self.ui.render_footer(f, chunks[3]);
-            })?;
-
-            if let Some(action) = rx.recv().await {
-                match action {
-                    Action::Select => {
-                        let selected_action = self.menu.select();
-                        self.handle_action(selected_action);
+    /// Runs until the user quits. Key presses are handled as soon as they
+    /// arrive and redrawn right away; task events only mark the screen as
+    /// changed, so a fast stream of download progress is drawn at most once per
+    /// render tick.
+    pub async fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let mut events = EventStream::new();
+        let mut render_tick = time::interval(RENDER_TICK);
+        render_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        self.draw(terminal)?;
+        let mut changed = false;
+        while !self.should_quit {
+            tokio::select! {
+                event = events.next() => match event {
+                    Some(Ok(event)) => {
+                        if let Some(action) = self.action_for(event) {
+                            self.handle_key_action(action);
+                            self.draw(terminal)?;
+                            changed = false;
+                        }
                     }
-                    Action::Next => self.menu.next(),
-                    Action::Previous => self.menu.previous(),
-                    Action::Resize => {
-                        self.update_ui(terminal)?;
+                    Some(Err(e)) => return Err(e.into()),
+                    None => break,
+                },
+                Some(event) = self.tasks.next() => {
+                    self.handle_task_event(event);
+                    changed = true;
+                }
+                _ = render_tick.tick(), if changed => {
+                    self.draw(terminal)?;
+                    changed = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The action a terminal event maps to. Typed characters go to the
+    /// popup being typed into, if any, and through the key bindings otherwise.
+    fn action_for(&self, event: Event) -> Option<Action> {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => match key.code {
+                KeyCode::Char(c) if self.is_typing() => Some(Action::Input(c)),
+                KeyCode::Backspace => Some(Action::Backspace),
+                code => self.ui.key_bindings.get_action(code).cloned(),
+            },
+            Event::Resize(_, _) => Some(Action::Resize),
+            _ => None,
+        }
+    }
+
+    /// Whether a popup with a text input has focus.
+    fn is_typing(&self) -> bool {
+        self.password_prompt.is_some()
+            || self
+                .file_picker
+                .as_ref()
+                .is_some_and(|picker| picker.is_entering_sha256())
+    }
+
+    fn handle_key_action(&mut self, action: Action) {
+        let confirming_quit = std::mem::take(&mut self.confirm_quit);
+        if self.handle_popup_action(&action) || self.handle_screen_action(&action) {
+            return;
+        }
+
+        match action {
+            Action::Select => self.screens.push(self.menu.select().screen(&self.config)),
+            Action::Next => self.menu.next(),
+            Action::Previous => self.menu.previous(),
+            // The next draw picks up the new size.
+            Action::Resize => {}
+            Action::Quit => self.quit(confirming_quit),
+            Action::Cancel => self.cancel_tasks(),
+            Action::Back | Action::Delete | Action::Input(_) | Action::Backspace => {}
+        }
+    }
+
+    /// Routes `action` to the open popup, if any. Returns whether it was consumed.
+    fn handle_popup_action(&mut self, action: &Action) -> bool {
+        if let Some((prompt, _)) = self.password_prompt.as_mut() {
+            match action {
+                // The password typed so far stays until the check running
+                // now has finished.
+                Action::Select if self.tasks.is_running(Task::Authenticate) => {}
+                Action::Select => {
+                    let password = prompt.take();
+                    self.spawn(
+                        Task::Authenticate,
+                        "Checking the sudo password...".to_string(),
+                        |_| async move {
+                            let result = tokio::task::spawn_blocking(move || {
+                                elevate::check_sudo_password(&password)
+                                    .map(|()| password)
+                                    .map_err(|e| e.to_string())
+                            })
+                            .await
+                            .unwrap_or_else(|e| Err(e.to_string()));
+                            TaskResult::Authenticated(result)
+                        },
+                    );
+                }
+                Action::Input(c) => prompt.push(*c),
+                Action::Backspace => prompt.pop(),
+                Action::Back => self.password_prompt = None,
+                Action::Next | Action::Previous | Action::Delete => {}
+                Action::Resize | Action::Cancel | Action::Quit => return false,
+            }
+            return true;
+        }
+
+        if let Some((dialog, _)) = self.plan_dialog.as_mut() {
+            match action {
+                Action::Select => {
+                    let elevator = dialog.elevator();
+                    if let Some((_, planned)) = self.plan_dialog.take() {
+                        self.run_planned(planned, elevator);
                     }
-                    Action::Quit => self.should_quit = true,
                 }
+                Action::Next => dialog.next(),
+                Action::Previous => dialog.previous(),
+                Action::Back => self.plan_dialog = None,
+                Action::Quit
+                | Action::Resize
+                | Action::Delete
+                | Action::Cancel
+                | Action::Input(_)
+                | Action::Backspace => return false,
             }
+            return true;
+        }
 
-            if self.should_quit {
-                break;
+        if let Some(picker) = self.channel_picker.as_mut() {
+            match action {
+                Action::Select => {
+                    self.channel = picker.select();
+                    self.channel_picker = None;
+                    self.ui
+                        .set_update_message(format!("Selected channel: {}", self.channel));
+                }
+                Action::Next => picker.next(),
+                Action::Previous => picker.previous(),
+                Action::Back => self.channel_picker = None,
+                Action::Quit
+                | Action::Resize
+                | Action::Delete
+                | Action::Cancel
+                | Action::Input(_)
+                | Action::Backspace => return false,
             }
+            return true;
         }
 
-        Ok(())
+        if let Some(picker) = self.file_picker.as_mut() {
+            match action {
+                Action::Select => match picker.select() {
+                    Ok(Some((file, verify))) => {
+                        self.file_picker = None;
+                        self.confirm(PlannedAction::Install(InstallSource::File(file, verify)));
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.ui.set_update_message(e),
+                },
+                Action::Next => picker.next(),
+                Action::Previous => picker.previous(),
+                Action::Input(c) => picker.push(*c),
+                Action::Backspace => picker.pop(),
+                Action::Back => {
+                    if !picker.back() {
+                        self.file_picker = None;
+                    }
+                }
+                Action::Resize | Action::Delete | Action::Cancel | Action::Quit => return false,
+            }
+            return true;
+        }
+
+        if let Some(dialog) = self.uninstall_dialog.as_mut() {
+            match action {
+                Action::Select => match dialog.select() {
+                    Ok(Some(plan)) => {
+                        let plan = plan.clone();
+                        self.uninstall_dialog = None;
+                        let elevator = plan.plan().elevator();
+                        self.run_planned(PlannedAction::Uninstall(plan), elevator);
+                    }
+                    Ok(None) => {}
+                    Err(e) => self
+                        .ui
+                        .set_update_message(format!("Failed to plan the uninstall: {}", e)),
+                },
+                Action::Next => dialog.next(),
+                Action::Previous => dialog.previous(),
+                Action::Back => self.uninstall_dialog = None,
+                Action::Quit
+                | Action::Resize
+                | Action::Delete
+                | Action::Cancel
+                | Action::Input(_)
+                | Action::Backspace => return false,
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Routes `action` to the open screen, if any. Returns whether it was
+    /// consumed; Esc goes back to the screen or menu below.
+    fn handle_screen_action(&mut self, action: &Action) -> bool {
+        if *action == Action::Back {
+            return self.screens.pop().is_some();
+        }
+        let event = match self.screens.top_mut() {
+            Some(screen) => screen.handle(action),
+            None => return false,
+        };
+
+        match event {
+            ScreenEvent::Ignored => return false,
+            ScreenEvent::Handled => {}
+            ScreenEvent::Push(screen) => self.screens.push(screen),
+            ScreenEvent::Run(action) => self.handle_action(action),
+        }
+        true
+    }
+
+    /// Takes the install lock, showing why in the info pane when another
+    /// operation holds it.
+    fn lock(&mut self) -> Option<InstallLock> {
+        match InstallLock::acquire() {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                self.ui.set_update_message(e.to_string());
+                None
+            }
+        }
+    }
+
+    fn switch_version(&mut self, version: NvimVersion) {
+        let message = format!("Switching to Neovim {}...", version);
+        self.spawn_change(Task::Switch, message, move || {
+            let store = VersionStore::new();
+            let previous = store.current().ok().flatten();
+            let result = store.switch(&version);
+            history::record_result(
+                HistoryAction::Switch,
+                previous,
+                Some(version.clone()),
+                &result,
+            );
+            match result {
+                Ok(_) => Ok(format!("Switched to Neovim {}", version)),
+                Err(e) => Err(format!("Failed to switch to Neovim {}: {}", version, e)),
+            }
+        });
+    }
+
+    fn remove_version(&mut self, version: NvimVersion) {
+        let message = format!("Removing Neovim {}...", version);
+        self.spawn_change(Task::Remove, message, move || {
+            let result = VersionStore::new().remove(&version);
+            history::record_result(HistoryAction::Remove, Some(version.clone()), None, &result);
+            match result {
+                Ok(()) => Ok(format!("Removed Neovim {}", version)),
+                Err(e) => Err(format!("Failed to remove Neovim {}: {}", version, e)),
+            }
+        });
+    }
+
+    /// Returns whether the rollback was started.
+    fn rollback(&mut self) -> bool {
+        self.spawn_change(Task::Rollback, "Rolling back...".to_string(), || {
+            let store = VersionStore::new();
+            let previous = store.current().ok().flatten();
+            let result = store.rollback();
+            let (old, new) = match &result {
+                Ok((from, to)) => (Some(from.clone()), Some(to.clone())),
+                Err(_) => (previous, None),
+            };
+            history::record_result(HistoryAction::Rollback, old, new, &result);
+            match result {
+                Ok((from, to)) => Ok(format!("Rolled back from Neovim {} to {}", from, to)),
+                Err(e) => Err(format!("Rollback failed: {}", e)),
+            }
+        })
+    }
+
+    /// Reloads the open screens and the previews after the installation
+    /// changed.
+    fn refresh_screens(&mut self) {
+        self.screens.refresh();
+        for preview in &mut self.previews {
+            preview.refresh();
+        }
     }
 
     fn handle_action(&mut self, action: MenuAction) {
         match action {
-            MenuAction::InstallNeovimNightly => {
-                self.ui
-                    .set_update_message("установка обновлений Neovim...".to_string());
+            MenuAction::InstallNeovim => {
+                self.confirm(PlannedAction::Install(InstallSource::Release(None)))
+            }
+            MenuAction::BuildFromSource => {
+                self.confirm(PlannedAction::Install(InstallSource::Source))
             }
+            MenuAction::InstallFromFile => self.file_picker = Some(FilePicker::new()),
             MenuAction::CheckForUpdates => {
-                self.ui
-                    .set_update_message("Проверка доступных обновлений...".to_string());
+                let channel = self.channel.clone();
+                self.spawn(
+                    Task::CheckUpdates,
+                    format!("Checking for updates of Neovim {}...", channel),
+                    |_| async move {
+                        TaskResult::UpdateCheck(
+                            check_for_updates(&channel).await.map_err(|e| e.to_string()),
+                        )
+                    },
+                );
+            }
+            MenuAction::ChooseChannel => {
+                self.spawn(
+                    Task::FetchReleases,
+                    "Fetching releases...".to_string(),
+                    |_| async {
+                        TaskResult::Releases(fetch_releases().await.map_err(|e| e.to_string()))
+                    },
+                );
+            }
+            MenuAction::Rollback => self.confirm(PlannedAction::Rollback),
+            MenuAction::SwitchVersion(version) => self.switch_version(version),
+            MenuAction::RemoveVersion(version) => self.remove_version(version),
+            MenuAction::Uninstall => match UninstallDialog::new(&self.config) {
+                Ok(dialog) => self.uninstall_dialog = Some(dialog),
+                Err(e) => self
+                    .ui
+                    .set_update_message(format!("Failed to plan the uninstall: {}", e)),
+            },
+        }
+    }
+
+    /// Starts `work` as `task`, showing `message` until it reports back.
+    /// Returns whether it was started.
+    fn spawn<F, Fut>(&mut self, task: Task, message: String, work: F) -> bool
+    where
+        F: FnOnce(TaskContext) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        let started = self.tasks.spawn(task, work);
+        if started {
+            self.ui.set_update_message(message);
+        } else {
+            self.ui
+                .set_update_message(format!("The {} is still running", task));
+        }
+        started
+    }
+
+    /// Plans `action` in the background; the plan is shown for confirmation
+    /// once it is ready.
+    fn confirm(&mut self, action: PlannedAction) {
+        if self.tasks.is_running(Task::Plan) {
+            return;
+        }
+        let (channel, config) = (self.channel.clone(), self.config.clone());
+        self.spawn(Task::Plan, "Planning...".to_string(), |_| async move {
+            TaskResult::Planned(
+                plan(action, &channel, &config)
+                    .await
+                    .map_err(|e| e.to_string()),
+            )
+        });
+    }
+
+    /// Quits, unless the installation is being changed: then the first quit
+    /// only asks to quit again, which cancels the change.
+    fn quit(&mut self, confirmed: bool) {
+        if confirmed || !self.tasks.is_changing_installation() {
+            self.should_quit = true;
+            return;
+        }
+        self.confirm_quit = true;
+        self.ui.set_update_message(
+            "Neovim is being installed or changed; press q again to quit and cancel it".to_string(),
+        );
+    }
+
+    fn cancel_tasks(&mut self) {
+        if self.tasks.is_idle() {
+            return;
+        }
+        self.tasks.cancel_all();
+        self.ui.set_update_message("Cancelling...".to_string());
+    }
+
+    /// Runs a confirmed action, first asking for the sudo password when it
+    /// needs root through sudo and sudo would otherwise prompt on the terminal.
+    /// Elevators that would prompt on the terminal themselves are refused, as
+    /// their prompt can't be answered while the TUI owns it.
+    fn run_planned(&mut self, action: PlannedAction, elevator: Option<Elevator>) {
+        match elevator {
+            Some(elevator) if elevator.needs_password() => {
+                self.password_prompt = Some((PasswordPrompt::new(), action));
             }
-            MenuAction::CheckDependencies => {
-                self.ui
-                    .set_update_message("Check dependencies...".to_string());
+            Some(elevator) if elevator.prompts_on_terminal() => {
+                self.ui.set_update_message(format!(
+                    "{} would ask for a password on the terminal; run neviraller from a shell for this, or use sudo",
+                    elevator
+                ));
             }
-            MenuAction::Quit => {
-                self.should_quit = true;
+            _ => self.execute(action),
+        }
+    }
+
+    /// Starts `action`. The sudo password, if one was given for it, is
+    /// forgotten once the action is done, or right away when it didn't start.
+    fn execute(&mut self, action: PlannedAction) {
+        let started = match action {
+            PlannedAction::Install(source) => self.start_install(source),
+            PlannedAction::Rollback => self.rollback(),
+            PlannedAction::Uninstall(plan) => self.uninstall(plan),
+        };
+        if !started {
+            elevate::clear_sudo_password();
+        }
+    }
+
+    /// Returns whether the uninstall was started.
+    fn uninstall(&mut self, plan: UninstallPlan) -> bool {
+        let message = "Uninstalling Neovim...".to_string();
+        self.spawn_change(Task::Uninstall, message, move || match plan.run() {
+            Ok(()) => Ok("Neovim has been uninstalled".to_string()),
+            Err(e) => Err(format!("Uninstall failed: {}", e)),
+        })
+    }
+
+    /// Runs `change` as `task` on a blocking thread that holds the install
+    /// lock, so removing directories and validating binaries doesn't stall
+    /// the UI. Returns whether it was started.
+    fn spawn_change<F>(&mut self, task: Task, message: String, change: F) -> bool
+    where
+        F: FnOnce() -> std::result::Result<String, String> + Send + 'static,
+    {
+        self.spawn(task, message, |_| async move {
+            let result = lock::spawn_blocking(move || {
+                let _lock = InstallLock::acquire().map_err(|e| e.to_string())?;
+                change()
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+            TaskResult::Changed(result)
+        })
+    }
+
+    /// Installs Neovim from `source` as `Task::Install`, holding the install
+    /// lock until it finishes or is cancelled. Returns whether it was started.
+    fn start_install(&mut self, source: InstallSource) -> bool {
+        if self.tasks.is_running(Task::Install) {
+            return false;
+        }
+        let Some(lock) = self.lock() else {
+            return false;
+        };
+        self.ui.clear_build_log();
+        let message = match &source {
+            InstallSource::Release(Some(choice)) => format!(
+                "Installing Neovim {} ({})... (c: cancel)",
+                choice.version, choice.channel
+            ),
+            InstallSource::Release(None) => {
+                format!("Installing Neovim {}... (c: cancel)", self.channel)
+            }
+            InstallSource::Source => format!("Building Neovim {}... (c: cancel)", self.channel),
+            InstallSource::File(file, _) => {
+                format!("Installing Neovim from {}... (c: cancel)", file.display())
+            }
+        };
+
+        let channel = self.channel.clone();
+        let config = self.config.clone();
+        self.spawn(Task::Install, message, |context| async move {
+            let _lock = lock;
+            let result = match &source {
+                InstallSource::Release(Some(choice)) => {
+                    update_neovim(choice, &config, Some(&context.progress)).await
+                }
+                InstallSource::Release(None) => match choose_release(&channel, &config).await {
+                    Ok(choice) => update_neovim(&choice, &config, Some(&context.progress)).await,
+                    Err(e) => Err(e),
+                },
+                InstallSource::Source => {
+                    build_neovim(&channel, &config, Some(&context.progress), &context.log).await
+                }
+                InstallSource::File(file, verify) => install_file(file, verify, &config).await,
+            }
+            .map_err(|e| match &source {
+                InstallSource::File(file, _) => {
+                    format!("Failed to install Neovim from {}: {}", file.display(), e)
+                }
+                _ => format!("Failed to install Neovim {}: {}", channel, e),
+            });
+            TaskResult::Installed(result)
+        })
+    }
+
+    fn handle_task_event(&mut self, event: TaskEvent) {
+        let (task, result) = match event {
+            TaskEvent::Progress(progress) => return self.ui.set_download_progress(progress),
+            TaskEvent::Log(line) => {
+                self.ui.clear_download_progress();
+                return self.ui.push_build_log(line);
             }
+            TaskEvent::Finished(task, result) => (task, *result),
+        };
+        if task.changes_installation() {
+            elevate::clear_sudo_password();
+            self.ui.clear_download_progress();
+            self.refresh_screens();
         }
+
+        let message = match result {
+            TaskResult::UpdateCheck(Ok(message)) => message,
+            TaskResult::UpdateCheck(Err(e)) => format!("Failed to check for updates: {}", e),
+            TaskResult::Releases(Ok((releases, installed))) => {
+                self.channel_picker = Some(ChannelPicker::new(&releases, installed));
+                format!("Current channel: {}", self.channel)
+            }
+            TaskResult::Releases(Err(e)) => format!("Failed to fetch releases: {}", e),
+            TaskResult::Planned(Ok((plan, action))) => {
+                self.plan_dialog = Some((PlanDialog::new(plan), action));
+                "Review the plan and confirm it to continue".to_string()
+            }
+            TaskResult::Planned(Err(e)) => format!("Failed to plan: {}", e),
+            TaskResult::Installed(Ok(outcome)) => {
+                let mut lines = vec![format!(
+                    "Neovim {} has been installed to {}",
+                    outcome.version,
+                    outcome.target.display()
+                )];
+                if let Some(replaced) = outcome.replaced {
+                    lines.push(format!(
+                        "Replaced Neovim {}; use Rollback to restore it",
+                        replaced
+                    ));
+                }
+                lines.extend(
+                    outcome
+                        .warnings
+                        .into_iter()
+                        .map(|warning| format!("Warning: {}", warning)),
+                );
+                lines.join("\n")
+            }
+            TaskResult::Installed(Err(e)) => e,
+            TaskResult::Changed(Ok(message) | Err(message)) => message,
+            TaskResult::Authenticated(Ok(password)) => {
+                // The prompt is gone when it was dismissed during the check.
+                if let Some((_, planned)) = self.password_prompt.take() {
+                    elevate::set_sudo_password(password);
+                    self.execute(planned);
+                }
+                return;
+            }
+            TaskResult::Authenticated(Err(e)) => e,
+            TaskResult::Cancelled => format!("Cancelled the {}", task),
+        };
+        self.ui.set_update_message(message);
     }
 
-    fn update_ui<B: Backend>(&self, terminal: &mut Terminal<B>) -> Result<()> {
+    fn draw<B: Backend>(&self, terminal: &mut Terminal<B>) -> Result<()> {
         terminal.draw(|f| {
             let size = f.size();
             let chunks = Layout::default()
@@ -120,54 +684,98 @@ impl App {
                 .constraints([
                     Constraint::Length(3),
                     Constraint::Percentage(50),
-                    Constraint::Percentage(45),
-                    Constraint::Length(2),
+                    Constraint::Percentage(50),
+                    Constraint::Length(3),
                 ])
                 .split(size);
 
             self.ui.render_header(f, chunks[0]);
-            self.ui.render_menu(f, chunks[1], &self.menu);
-            if self.ui.show_update_message {
-                self.ui
-                    .render_additional_info(f, chunks[2], &self.ui.update_message);
-            }
+            let (screen, focused) = match self.screens.top() {
+                Some(screen) => (screen, true),
+                None => (self.previews[self.menu.selected()].as_ref(), false),
+            };
+            self.ui
+                .render_menu(f, chunks[1], &self.menu, screen, focused);
+            self.ui
+                .render_additional_info(f, chunks[2], &self.ui.update_message);
             self.ui.render_footer(f, chunks[3]);
+            if let Some(picker) = &self.channel_picker {
+                self.ui.render_channel_picker(f, size, picker);
+            }
+            if let Some(picker) = &self.file_picker {
+                self.ui.render_file_picker(f, size, picker);
+            }
+            if let Some(dialog) = &self.uninstall_dialog {
+                self.ui.render_uninstall_dialog(f, size, dialog);
+            }
+            if let Some((dialog, _)) = &self.plan_dialog {
+                self.ui.render_plan_dialog(f, size, dialog);
+            }
+            if let Some((prompt, _)) = &self.password_prompt {
+                self.ui.render_password_prompt(f, size, prompt);
+            }
         })?;
         Ok(())
     }
 }
 
-pub async fn event_handler(tx: mpsc::UnboundedSender<Action>, key_bindings: KeyBindings) {
-    let mut interval = time::interval(Duration::from_millis(100));
-    loop {
-        interval.tick().await;
-        if let Ok(true) = poll(Duration::from_millis(0)) {
-            match read() {
-                Ok(Event::Key(key)) => {
-                    if let Some(action) = key_bindings.get_action(key.code) {
-                        if let Err(e) = tx.send(action.clone()) {
-                            eprintln!("Error sending action: {:?}", e);
-                            break;
-                        }
-                    }
-                }
-                Ok(Event::Mouse(_)) => {
-                    // Обработка событий мыши
-                }
-                Ok(Event::Resize(_, _)) => {
-                    tx.send(Action::Resize).unwrap();
-                }
-                Ok(Event::FocusGained) | Ok(Event::FocusLost) => {
-                    // Обработка событий фокуса
-                }
-                Ok(Event::Paste(_)) => {
-                    // Обработка вставки из буфера обмена
-                }
-                Err(e) => {
-                    eprintln!("Error reading event: {:?}", e);
-                    continue;
-                } // _ => {} // Обработка других неучтенных событий
-            }
+/// The latest releases and the installed version, for the channel picker.
+async fn fetch_releases() -> crate::neovim_nightly::Result<(Vec<Release>, Option<NvimVersion>)> {
+    let releases = ReleaseSource::new()?.releases(10).await?;
+    Ok((releases, installed_version().ok().flatten()))
+}
+
+async fn check_for_updates(channel: &Channel) -> crate::neovim_nightly::Result<String> {
+    let release = ReleaseSource::new()?.release(channel).await?;
+    let latest = release
+        .version()
+        .ok_or("Couldn't find information about new version")?;
+
+    Ok(match check_neovim_version(&latest)? {
+        UpdateCheck::NotInstalled => {
+            format!("Neovim is not installed. Latest {}: {}", channel, latest)
         }
-    }
+        UpdateCheck::UpToDate(installed) => {
+            format!(
+                "You are already using the latest Neovim version: {}",
+                installed
+            )
+        }
+        UpdateCheck::Available(installed) => format!(
+            "There is a newer version of Neovim {} available: {} (installed: {})",
+            channel, latest, installed
+        ),
+        UpdateCheck::Downgrade(installed) => format!(
+            "Installed Neovim {} is newer than the latest {}: {}",
+            installed, channel, latest
+        ),
+    })
+}
+
+/// What `action` is going to do, and the action to run once the plan is
+/// confirmed. An install from a release settles on the release asset here, so
+/// the install uses the one the plan shows.
+async fn plan(
+    action: PlannedAction,
+    channel: &Channel,
+    config: &Config,
+) -> crate::neovim_nightly::Result<(Plan, PlannedAction)> {
+    let plan = match &action {
+        PlannedAction::Install(InstallSource::Release(None)) => {
+            let choice = choose_release(channel, config).await?;
+            let plan = plan_update(&choice, config)?;
+            let action = PlannedAction::Install(InstallSource::Release(Some(Box::new(choice))));
+            return Ok((plan, action));
+        }
+        PlannedAction::Install(InstallSource::Release(Some(choice))) => {
+            plan_update(choice, config)?
+        }
+        PlannedAction::Install(InstallSource::Source) => plan_build(channel, config)?,
+        PlannedAction::Install(InstallSource::File(file, verify)) => {
+            plan_install_file(file, verify, config).await?
+        }
+        PlannedAction::Rollback => VersionStore::new().plan_rollback()?,
+        PlannedAction::Uninstall(plan) => plan.plan(),
+    };
+    Ok((plan, action))
 }
@@ -0,0 +1,108 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::neovim_nightly::{channel::Channel, release::Release, version::NvimVersion};
+
+use super::list;
+
+pub struct ChannelEntry {
+    pub channel: Channel,
+    pub latest: Option<NvimVersion>,
+}
+
+pub struct ChannelPicker {
+    entries: Vec<ChannelEntry>,
+    installed: Option<NvimVersion>,
+    state: ListState,
+}
+
+impl ChannelPicker {
+    pub fn new(releases: &[Release], installed: Option<NvimVersion>) -> ChannelPicker {
+        let latest = |tag: &str| {
+            releases
+                .iter()
+                .find(|release| release.tag_name == tag)
+                .and_then(Release::version)
+        };
+
+        let mut entries = vec![
+            ChannelEntry {
+                channel: Channel::Nightly,
+                latest: latest(Channel::Nightly.tag()),
+            },
+            ChannelEntry {
+                channel: Channel::Stable,
+                latest: latest(Channel::Stable.tag()),
+            },
+        ];
+        entries.extend(
+            releases
+                .iter()
+                .filter(|release| release.tag_name.starts_with('v'))
+                .map(|release| ChannelEntry {
+                    channel: Channel::Tag(release.tag_name.clone()),
+                    latest: release.version(),
+                }),
+        );
+
+        let mut state = ListState::default();
+        state.select(Some(0));
+        ChannelPicker {
+            entries,
+            installed,
+            state,
+        }
+    }
+
+    pub fn next(&mut self) {
+        list::select_next(&mut self.state, self.entries.len());
+    }
+
+    pub fn previous(&mut self) {
+        list::select_previous(&mut self.state, self.entries.len());
+    }
+
+    pub fn select(&self) -> Channel {
+        self.entries[self.state.selected().unwrap_or(0)]
+            .channel
+            .clone()
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let latest = entry
+                    .latest
+                    .as_ref()
+                    .map_or("unknown".to_string(), NvimVersion::to_string);
+                let newer = match (&entry.latest, &self.installed) {
+                    (Some(latest), Some(installed)) if latest > installed => " (newer)",
+                    _ => "",
+                };
+                ListItem::new(format!(
+                    "{:<10} {}{}",
+                    entry.channel.to_string(),
+                    latest,
+                    newer
+                ))
+            })
+            .collect();
+
+        let title = format!(
+            "Channel (installed: {})",
+            self.installed
+                .as_ref()
+                .map_or("none".to_string(), NvimVersion::to_string)
+        );
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut self.state.clone());
+    }
+}
@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::neovim_nightly::{
+    checksum, paths,
+    update::{artifact_format, FileChecksum},
+};
+
+use super::list;
+
+/// Neovim AppImages and tarballs found in the working directory and
+/// `~/Downloads`, to install without a network connection. A file without a
+/// checksum next to it asks for its SHA-256 before it is installed.
+pub struct FilePicker {
+    files: Vec<PathBuf>,
+    /// Whether each file has a `.sha256sum` or `shasum.txt` next to it.
+    has_checksum: Vec<bool>,
+    state: ListState,
+    /// The SHA-256 being typed for the selected file.
+    sha256: Option<String>,
+}
+
+impl FilePicker {
+    pub fn new() -> FilePicker {
+        let mut dirs = vec![paths::home_dir().join("Downloads")];
+        if let Ok(cwd) = std::env::current_dir() {
+            dirs.insert(0, cwd);
+        }
+        dirs.dedup();
+
+        let files: Vec<PathBuf> = dirs.iter().flat_map(|dir| find_artifacts(dir)).collect();
+        let has_checksum = files
+            .iter()
+            .map(|file| checksum::has_local_sha256(file))
+            .collect();
+        let mut state = ListState::default();
+        state.select((!files.is_empty()).then_some(0));
+        FilePicker {
+            files,
+            has_checksum,
+            state,
+            sha256: None,
+        }
+    }
+
+    /// Moves to the next file; the selection stays put while a SHA-256 is
+    /// typed, so it can't end up applied to another file.
+    pub fn next(&mut self) {
+        if !self.is_entering_sha256() {
+            list::select_next(&mut self.state, self.files.len());
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.is_entering_sha256() {
+            list::select_previous(&mut self.state, self.files.len());
+        }
+    }
+
+    /// The selected file and how to check it. A file without a checksum next
+    /// to it first opens the SHA-256 input, where an empty entry installs it
+    /// unverified.
+    pub fn select(&mut self) -> Result<Option<(PathBuf, FileChecksum)>, String> {
+        let Some(selected) = self.state.selected() else {
+            return Ok(None);
+        };
+        let Some(file) = self.files.get(selected).cloned() else {
+            return Ok(None);
+        };
+
+        let verify = match self.sha256.as_deref() {
+            None if self.has_checksum[selected] => FileChecksum::Sidecar,
+            None => {
+                self.sha256 = Some(String::new());
+                return Ok(None);
+            }
+            Some("") => FileChecksum::Unverified,
+            Some(sha256) => match checksum::parse_sha256(sha256) {
+                Some(sha256) => FileChecksum::Sha256(sha256),
+                None => return Err(format!("{:?} is not a SHA-256", sha256)),
+            },
+        };
+        Ok(Some((file, verify)))
+    }
+
+    pub fn is_entering_sha256(&self) -> bool {
+        self.sha256.is_some()
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(sha256) = self.sha256.as_mut() {
+            sha256.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(sha256) = self.sha256.as_mut() {
+            sha256.pop();
+        }
+    }
+
+    /// Leaves the SHA-256 input. Returns false when it wasn't open.
+    pub fn back(&mut self) -> bool {
+        self.sha256.take().is_some()
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = if self.files.is_empty() {
+            vec![ListItem::new(
+                "No nvim AppImage or tarball in this directory or ~/Downloads",
+            )]
+        } else {
+            self.files
+                .iter()
+                .zip(&self.has_checksum)
+                .map(|(file, has_checksum)| {
+                    let note = if *has_checksum { "" } else { "  (no checksum)" };
+                    ListItem::new(format!("{}{}", file.display(), note))
+                })
+                .collect()
+        };
+
+        let area = match &self.sha256 {
+            Some(sha256) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(3), Constraint::Length(3)])
+                    .split(area);
+                let input =
+                    Paragraph::new(sha256.as_str())
+                        .block(Block::default().borders(Borders::ALL).title(
+                        "SHA-256 (Enter: verify and install, empty: install unverified, Esc: back)",
+                    ));
+                frame.render_widget(input, chunks[1]);
+                chunks[0]
+            }
+            None => area,
+        };
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Install from file (Enter: install)"),
+            )
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut self.state.clone());
+    }
+}
+
+fn find_artifacts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("nvim") && artifact_format(name).is_some())
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    files
+}
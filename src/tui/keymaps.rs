@@ -42,8 +42,10 @@ impl KeyBindings {
         bindings.insert(OrdKeyCode(KeyCode::Char('p')), Action::Previous);
         bindings.insert(OrdKeyCode(KeyCode::Up), Action::Previous);
         bindings.insert(OrdKeyCode(KeyCode::Enter), Action::Select);
+        bindings.insert(OrdKeyCode(KeyCode::Char('d')), Action::Delete);
+        bindings.insert(OrdKeyCode(KeyCode::Char('c')), Action::Cancel);
         bindings.insert(OrdKeyCode(KeyCode::Char('q')), Action::Quit);
-        bindings.insert(OrdKeyCode(KeyCode::Esc), Action::Quit);
+        bindings.insert(OrdKeyCode(KeyCode::Esc), Action::Back);
 
         Self { bindings }
     }
@@ -0,0 +1,27 @@
+use ratatui::widgets::ListState;
+
+/// Moves the selection in a list of `len` items down one, wrapping around to
+/// the first item.
+pub fn select_next(state: &mut ListState, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let next_index = match state.selected() {
+        Some(selected) if selected + 1 < len => selected + 1,
+        _ => 0,
+    };
+    state.select(Some(next_index));
+}
+
+/// Moves the selection in a list of `len` items up one, wrapping around to the
+/// last item.
+pub fn select_previous(state: &mut ListState, len: usize) {
+    if len == 0 {
+        return;
+    }
+    let prev_index = match state.selected() {
+        Some(0) | None => len - 1,
+        Some(selected) => selected - 1,
+    };
+    state.select(Some(prev_index));
+}
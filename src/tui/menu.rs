@@ -4,36 +4,84 @@ use ratatui::{
     Frame,
 };
 
-#[derive(Clone, Copy)]
+use crate::neovim_nightly::{config::Config, version::NvimVersion};
+
+use super::{
+    list,
+    screens::{
+        actions::ActionScreen, dependencies::DependenciesScreen, history::HistoryScreen,
+        settings::SettingsScreen, versions::VersionsScreen, Screen,
+    },
+};
+
+/// An operation chosen on one of the screens.
+#[derive(Clone)]
 pub enum MenuAction {
-    InstallNeovimNightly,
+    InstallNeovim,
+    BuildFromSource,
+    InstallFromFile,
+    Uninstall,
     CheckForUpdates,
-    CheckDependencies,
-    Quit,
+    ChooseChannel,
+    Rollback,
+    SwitchVersion(NvimVersion),
+    RemoveVersion(NvimVersion),
+}
+
+/// A menu entry; each opens its own screen.
+#[derive(Clone, Copy)]
+pub enum Section {
+    Install,
+    Updates,
+    Versions,
+    History,
+    Dependencies,
+    Settings,
+}
+
+impl Section {
+    pub fn screen(self, config: &Config) -> Box<dyn Screen> {
+        match self {
+            Section::Install => Box::new(ActionScreen::install()),
+            Section::Updates => Box::new(ActionScreen::updates()),
+            Section::Versions => Box::new(VersionsScreen::new()),
+            Section::History => Box::new(HistoryScreen::new()),
+            Section::Dependencies => Box::new(DependenciesScreen::new()),
+            Section::Settings => Box::new(SettingsScreen::new(config)),
+        }
+    }
 }
 
 pub struct MenuItem {
     pub name: String,
-    pub action: MenuAction,
+    pub section: Section,
 }
 
 pub fn get_menu_items() -> Vec<MenuItem> {
     vec![
         MenuItem {
-            name: "Install Neovim Nightly".to_string(),
-            action: MenuAction::InstallNeovimNightly,
+            name: "Install".to_string(),
+            section: Section::Install,
+        },
+        MenuItem {
+            name: "Updates".to_string(),
+            section: Section::Updates,
+        },
+        MenuItem {
+            name: "Versions".to_string(),
+            section: Section::Versions,
         },
         MenuItem {
-            name: "Check for updates".to_string(),
-            action: MenuAction::CheckForUpdates,
+            name: "History".to_string(),
+            section: Section::History,
         },
         MenuItem {
             name: "Dependencies".to_string(),
-            action: MenuAction::CheckDependencies,
+            section: Section::Dependencies,
         },
         MenuItem {
-            name: "Quit".to_string(),
-            action: MenuAction::Quit,
+            name: "Settings".to_string(),
+            section: Section::Settings,
         },
     ]
 }
@@ -51,35 +99,24 @@ impl Menu {
     }
 
     pub fn next(&mut self) {
-        let next_index = match self.state.selected() {
-            Some(selected) => {
-                if selected >= self.items.len() - 1 {
-                    0
-                } else {
-                    selected + 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(next_index));
+        list::select_next(&mut self.state, self.items.len());
     }
 
     pub fn previous(&mut self) {
-        let prev_index = match self.state.selected() {
-            Some(selected) => {
-                if selected == 0 {
-                    self.items.len() - 1
-                } else {
-                    selected - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(prev_index));
+        list::select_previous(&mut self.state, self.items.len());
+    }
+
+    pub fn select(&self) -> Section {
+        self.items[self.selected()].section
+    }
+
+    /// Index of the selected entry.
+    pub fn selected(&self) -> usize {
+        self.state.selected().unwrap_or(0)
     }
 
-    pub fn select(&self) -> MenuAction {
-        self.items[self.state.selected().unwrap_or(0)].action
+    pub fn sections(&self) -> impl Iterator<Item = Section> + '_ {
+        self.items.iter().map(|item| item.section)
     }
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
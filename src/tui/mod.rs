@@ -1,21 +1,32 @@
 pub mod app;
+pub mod channel_picker;
+pub mod file_picker;
 pub mod keymaps;
+pub mod list;
 pub mod menu;
+pub mod password_prompt;
+pub mod plan_dialog;
+pub mod screens;
+pub mod tasks;
 pub mod ui;
+pub mod uninstall_dialog;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use crossterm::{
     execute,
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use tokio::sync::mpsc;
 
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::{error::Error, io};
 
-use self::{app::Action, keymaps::KeyBindings};
+use crate::neovim_nightly::config::Config;
+
+use self::keymaps::KeyBindings;
 
 pub async fn run_term() -> Result<()> {
+    let config = Config::load().map_err(|e| eyre!("Failed to load config: {}", e))?;
+
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -23,17 +34,8 @@ pub async fn run_term() -> Result<()> {
     let backend = setup_backend();
     let mut terminal = setup_terminal(backend.unwrap()).unwrap();
 
-    let key_bindings = KeyBindings::new(); 
-    let cloned_bindings = key_bindings.clone();
-
-    let (tx, rx) = mpsc::unbounded_channel::<Action>();
-
-    tokio::spawn(async move {
-        app::event_handler(tx, cloned_bindings).await; 
-    });
-
-    let mut app = app::App::new(key_bindings);
-    app.run(&mut terminal, rx).await?;
+    let mut app = app::App::new(KeyBindings::new(), config);
+    app.run(&mut terminal).await?;
 
     if let Err(e) = cleanup_terminal(&mut stdout) {
         eprintln!("Ошибка при очистке терминала: {:?}", e);
@@ -0,0 +1,42 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Masked input for the sudo password, which sudo can't prompt for itself
+/// inside the TUI.
+pub struct PasswordPrompt {
+    password: String,
+}
+
+impl PasswordPrompt {
+    pub fn new() -> PasswordPrompt {
+        PasswordPrompt {
+            password: String::new(),
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.password.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.password.pop();
+    }
+
+    /// Returns the entered password, leaving the input empty for a retry.
+    pub fn take(&mut self) -> String {
+        std::mem::take(&mut self.password)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let masked = "*".repeat(self.password.chars().count());
+        let input = Paragraph::new(masked).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("sudo password (Enter: confirm, Esc: cancel)"),
+        );
+        frame.render_widget(input, area);
+    }
+}
@@ -0,0 +1,66 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::neovim_nightly::{elevate::Elevator, plan::Plan};
+
+use super::list;
+
+/// Shows what an operation is going to do and asks for confirmation before
+/// it runs.
+pub struct PlanDialog {
+    plan: Plan,
+    lines: Vec<String>,
+    state: ListState,
+}
+
+impl PlanDialog {
+    pub fn new(plan: Plan) -> PlanDialog {
+        let lines = plan.describe();
+        let mut state = ListState::default();
+        state.select((!lines.is_empty()).then_some(0));
+        PlanDialog { plan, lines, state }
+    }
+
+    /// What the plan's root steps run through, if it has any.
+    pub fn elevator(&self) -> Option<Elevator> {
+        self.plan.elevator()
+    }
+
+    pub fn next(&mut self) {
+        list::select_next(&mut self.state, self.lines.len());
+    }
+
+    pub fn previous(&mut self) {
+        list::select_previous(&mut self.state, self.lines.len());
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let mut items: Vec<ListItem> = if self.lines.is_empty() {
+            vec![ListItem::new("Nothing to do")]
+        } else {
+            self.lines
+                .iter()
+                .map(|line| ListItem::new(line.as_str()))
+                .collect()
+        };
+        if let Some(elevator) = self.plan.elevator() {
+            items.push(ListItem::new(format!(
+                "Steps marked [{}] run as root",
+                elevator
+            )));
+        }
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("{} (Enter: confirm, Esc: cancel)", self.plan.title)),
+            )
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, area, &mut self.state.clone());
+    }
+}
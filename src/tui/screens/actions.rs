@@ -0,0 +1,139 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::tui::{app::Action, list, menu::MenuAction};
+
+use super::{block, list_state, Screen, ScreenEvent};
+
+struct ActionItem {
+    name: &'static str,
+    description: &'static str,
+    action: MenuAction,
+}
+
+/// A list of operations with a description of the selected one, used by the
+/// Install and Updates sections.
+pub struct ActionScreen {
+    title: &'static str,
+    items: Vec<ActionItem>,
+    state: ListState,
+}
+
+impl ActionScreen {
+    fn new(title: &'static str, items: Vec<ActionItem>) -> ActionScreen {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        ActionScreen {
+            title,
+            items,
+            state,
+        }
+    }
+
+    pub fn install() -> ActionScreen {
+        ActionScreen::new(
+            "Install",
+            vec![
+                ActionItem {
+                    name: "Install Neovim",
+                    description: "Downloads the release of the selected channel, verifies its \
+                                  checksum and switches to it.",
+                    action: MenuAction::InstallNeovim,
+                },
+                ActionItem {
+                    name: "Build from source",
+                    description: "Builds the selected channel from its source archive. Needs \
+                                  the tools listed under Dependencies.",
+                    action: MenuAction::BuildFromSource,
+                },
+                ActionItem {
+                    name: "Install from file",
+                    description: "Installs an AppImage or tarball that was downloaded \
+                                  beforehand.",
+                    action: MenuAction::InstallFromFile,
+                },
+                ActionItem {
+                    name: "Uninstall",
+                    description: "Removes Neovim and, if chosen, its config, data, state and \
+                                  cache directories.",
+                    action: MenuAction::Uninstall,
+                },
+            ],
+        )
+    }
+
+    pub fn updates() -> ActionScreen {
+        ActionScreen::new(
+            "Updates",
+            vec![
+                ActionItem {
+                    name: "Check for updates",
+                    description: "Compares the installed Neovim with the latest release of the \
+                                  selected channel.",
+                    action: MenuAction::CheckForUpdates,
+                },
+                ActionItem {
+                    name: "Choose channel",
+                    description: "Selects nightly, stable or a tagged release for installs and \
+                                  update checks.",
+                    action: MenuAction::ChooseChannel,
+                },
+                ActionItem {
+                    name: "Rollback",
+                    description: "Switches back to the version that was active before the last \
+                                  switch.",
+                    action: MenuAction::Rollback,
+                },
+            ],
+        )
+    }
+
+    fn next(&mut self) {
+        list::select_next(&mut self.state, self.items.len());
+    }
+
+    fn previous(&mut self) {
+        list::select_previous(&mut self.state, self.items.len());
+    }
+
+    fn selected(&self) -> &ActionItem {
+        &self.items[self.state.selected().unwrap_or(0)]
+    }
+}
+
+impl Screen for ActionScreen {
+    fn handle(&mut self, action: &Action) -> ScreenEvent {
+        match action {
+            Action::Select => return ScreenEvent::Run(self.selected().action.clone()),
+            Action::Next => self.next(),
+            Action::Previous => self.previous(),
+            _ => return ScreenEvent::Ignored,
+        }
+        ScreenEvent::Handled
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(4)])
+            .split(area);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.name))
+            .collect();
+        let list = List::new(items)
+            .block(block(self.title, focused))
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, rows[0], &mut list_state(&self.state, focused));
+
+        let description = Paragraph::new(self.selected().description)
+            .block(Block::default().borders(Borders::ALL))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(description, rows[1]);
+    }
+}
@@ -0,0 +1,85 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::neovim_nightly::{platform, source_build::BUILD_DEPENDENCIES};
+use crate::tui::app::Action;
+
+use super::{block, Screen, ScreenEvent};
+
+struct Dependency {
+    name: &'static str,
+    found: bool,
+    needed_for: &'static str,
+}
+
+/// Which of the programs installs rely on are available. Enter checks again.
+pub struct DependenciesScreen {
+    dependencies: Vec<Dependency>,
+}
+
+impl DependenciesScreen {
+    pub fn new() -> DependenciesScreen {
+        let mut screen = DependenciesScreen {
+            dependencies: Vec::new(),
+        };
+        screen.refresh();
+        screen
+    }
+}
+
+impl Screen for DependenciesScreen {
+    fn handle(&mut self, action: &Action) -> ScreenEvent {
+        match action {
+            Action::Select => self.refresh(),
+            _ => return ScreenEvent::Ignored,
+        }
+        ScreenEvent::Handled
+    }
+
+    fn refresh(&mut self) {
+        self.dependencies = BUILD_DEPENDENCIES
+            .into_iter()
+            .map(|program| Dependency {
+                name: program,
+                found: platform::has_program(program),
+                needed_for: "building from source",
+            })
+            .collect();
+        self.dependencies.push(Dependency {
+            name: "FUSE",
+            found: platform::fuse_available(),
+            needed_for: "running the AppImage; the tarball is installed without it",
+        });
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let lines: Vec<Line> = self
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                let (status, color) = if dependency.found {
+                    ("found  ", Color::Green)
+                } else {
+                    ("missing", Color::Red)
+                };
+                Line::from(vec![
+                    Span::styled(status, Style::default().fg(color)),
+                    Span::raw(format!(
+                        "  {:<8} {}",
+                        dependency.name, dependency.needed_for
+                    )),
+                ])
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines)
+            .block(block("Dependencies (Enter: check again)", focused))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, area);
+    }
+}
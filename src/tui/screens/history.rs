@@ -0,0 +1,122 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::neovim_nightly::history::{self, HistoryEntry};
+use crate::tui::{app::Action, list};
+
+use super::{block, list_state, Screen, ScreenEvent};
+
+/// The install history, newest entry first. Enter opens the selected entry.
+pub struct HistoryScreen {
+    entries: Result<Vec<HistoryEntry>, String>,
+    state: ListState,
+}
+
+impl HistoryScreen {
+    pub fn new() -> HistoryScreen {
+        let mut screen = HistoryScreen {
+            entries: Ok(Vec::new()),
+            state: ListState::default(),
+        };
+        screen.refresh();
+        screen
+    }
+
+    fn len(&self) -> usize {
+        self.entries.as_ref().map_or(0, Vec::len)
+    }
+
+    fn next(&mut self) {
+        let len = self.len();
+        list::select_next(&mut self.state, len);
+    }
+
+    fn previous(&mut self) {
+        let len = self.len();
+        list::select_previous(&mut self.state, len);
+    }
+
+    /// Path and artifact hash of the selected entry.
+    fn details(&self) -> Option<String> {
+        let entry = self.entries.as_ref().ok()?.get(self.state.selected()?)?;
+        let mut lines = vec![entry.summary()];
+        if let Some(path) = &entry.path {
+            lines.push(format!("Path: {}", path.display()));
+        }
+        if let Some(sha256) = &entry.sha256 {
+            lines.push(format!("sha256: {}", sha256));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+impl Screen for HistoryScreen {
+    fn handle(&mut self, action: &Action) -> ScreenEvent {
+        match action {
+            Action::Select => {
+                if let Some(details) = self.details() {
+                    return ScreenEvent::Push(Box::new(HistoryEntryScreen { details }));
+                }
+            }
+            Action::Next => self.next(),
+            Action::Previous => self.previous(),
+            _ => return ScreenEvent::Ignored,
+        }
+        ScreenEvent::Handled
+    }
+
+    /// Starts over at the newest entry; a new one may have been added on top.
+    fn refresh(&mut self) {
+        self.entries = history::load()
+            .map(|mut entries| {
+                entries.reverse();
+                entries
+            })
+            .map_err(|e| format!("Failed to read the history: {}", e));
+        self.state.select((self.len() > 0).then_some(0));
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = block("History (Enter: details)", focused);
+        let entries = match &self.entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                frame.render_widget(Paragraph::new(e.as_str()).block(block), area);
+                return;
+            }
+        };
+
+        let items: Vec<ListItem> = if entries.is_empty() {
+            vec![ListItem::new("Nothing recorded yet")]
+        } else {
+            entries
+                .iter()
+                .map(|entry| ListItem::new(entry.summary()))
+                .collect()
+        };
+
+        let list = List::new(items).block(block).highlight_symbol(">> ");
+        frame.render_stateful_widget(list, area, &mut list_state(&self.state, focused));
+    }
+}
+
+/// One history entry with its path and artifact hash.
+struct HistoryEntryScreen {
+    details: String,
+}
+
+impl Screen for HistoryEntryScreen {
+    fn handle(&mut self, _action: &Action) -> ScreenEvent {
+        ScreenEvent::Ignored
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let details = Paragraph::new(self.details.as_str())
+            .block(block("History entry", focused))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, area);
+    }
+}
@@ -0,0 +1,88 @@
+pub mod actions;
+pub mod dependencies;
+pub mod history;
+pub mod settings;
+pub mod versions;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, ListState},
+    Frame,
+};
+
+use super::{app::Action, menu::MenuAction};
+
+/// What a screen asks the app to do after handling a key.
+pub enum ScreenEvent {
+    /// The key means nothing to the screen; the app handles it as usual.
+    Ignored,
+    Handled,
+    /// Opens another screen on top of this one.
+    Push(Box<dyn Screen>),
+    Run(MenuAction),
+}
+
+/// A view in the content pane next to the menu. The selected menu entry's
+/// screen is previewed there; Enter opens it and hands it the keys, Esc goes
+/// back.
+pub trait Screen {
+    fn handle(&mut self, action: &Action) -> ScreenEvent;
+
+    /// Reloads what the screen shows after the installation changed.
+    fn refresh(&mut self) {}
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool);
+}
+
+/// The screens opened from the menu, the topmost one having focus. The menu has
+/// focus while the stack is empty.
+#[derive(Default)]
+pub struct ScreenStack {
+    screens: Vec<Box<dyn Screen>>,
+}
+
+impl ScreenStack {
+    pub fn push(&mut self, screen: Box<dyn Screen>) {
+        self.screens.push(screen);
+    }
+
+    pub fn pop(&mut self) -> Option<Box<dyn Screen>> {
+        self.screens.pop()
+    }
+
+    pub fn top(&self) -> Option<&dyn Screen> {
+        self.screens.last().map(|screen| screen.as_ref())
+    }
+
+    pub fn top_mut(&mut self) -> Option<&mut Box<dyn Screen>> {
+        self.screens.last_mut()
+    }
+
+    pub fn refresh(&mut self) {
+        for screen in &mut self.screens {
+            screen.refresh();
+        }
+    }
+}
+
+/// The border of a screen, highlighted while it has focus.
+pub fn block(title: &str, focused: bool) -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    if focused {
+        block
+            .title(format!("{} (Esc: back)", title))
+            .border_style(Style::default().fg(Color::Yellow))
+    } else {
+        block.title(title.to_string())
+    }
+}
+
+/// `state` as drawn; a preview doesn't show the selection.
+pub fn list_state(state: &ListState, focused: bool) -> ListState {
+    if focused {
+        state.clone()
+    } else {
+        ListState::default()
+    }
+}
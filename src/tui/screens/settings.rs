@@ -0,0 +1,75 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+use crate::neovim_nightly::config::Config;
+use crate::tui::app::Action;
+
+use super::{block, Screen, ScreenEvent};
+
+/// The settings in effect, as loaded from the config file at startup.
+pub struct SettingsScreen {
+    lines: Vec<String>,
+}
+
+impl SettingsScreen {
+    pub fn new(config: &Config) -> SettingsScreen {
+        let prefix = match &config.prefix {
+            Some(prefix) => prefix.display().to_string(),
+            None => format!("{} (default)", config.install_prefix().display()),
+        };
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+        let lines = vec![
+            format!("Install prefix:    {}", prefix),
+            format!(
+                "Format:            {}",
+                format!("{:?}", config.format).to_lowercase()
+            ),
+            format!("Extract AppImage:  {}", yes_no(config.appimage_extract)),
+            format!(
+                "Elevator:          {}",
+                format!("{:?}", config.elevator).to_lowercase()
+            ),
+            format!("Build type:        {}", config.build.build_type.as_str()),
+            format!(
+                "Build ref:         {}",
+                config
+                    .build
+                    .git_ref
+                    .as_deref()
+                    .unwrap_or("the channel's tag")
+            ),
+            format!(
+                "CMake flags:       {}",
+                if config.build.cmake_flags.is_empty() {
+                    "none".to_string()
+                } else {
+                    config.build.cmake_flags.join(" ")
+                }
+            ),
+            String::new(),
+            format!(
+                "Edit {} and restart to change these.",
+                Config::path().display()
+            ),
+        ];
+
+        SettingsScreen { lines }
+    }
+}
+
+impl Screen for SettingsScreen {
+    fn handle(&mut self, _action: &Action) -> ScreenEvent {
+        ScreenEvent::Ignored
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let paragraph = Paragraph::new(self.lines.join("\n"))
+            .block(block("Settings", focused))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, area);
+    }
+}
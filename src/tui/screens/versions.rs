@@ -0,0 +1,104 @@
+use ratatui::{
+    layout::Rect,
+    widgets::{List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::neovim_nightly::store::{InstalledVersion, VersionStore};
+use crate::tui::{app::Action, list, menu::MenuAction};
+
+use super::{block, list_state, Screen, ScreenEvent};
+
+/// The versions in the store; Enter switches to the selected one, d removes it.
+pub struct VersionsScreen {
+    versions: Result<Vec<InstalledVersion>, String>,
+    state: ListState,
+}
+
+impl VersionsScreen {
+    pub fn new() -> VersionsScreen {
+        let mut screen = VersionsScreen {
+            versions: Ok(Vec::new()),
+            state: ListState::default(),
+        };
+        screen.refresh();
+        screen
+    }
+
+    fn len(&self) -> usize {
+        self.versions.as_ref().map_or(0, Vec::len)
+    }
+
+    fn next(&mut self) {
+        let len = self.len();
+        list::select_next(&mut self.state, len);
+    }
+
+    fn previous(&mut self) {
+        let len = self.len();
+        list::select_previous(&mut self.state, len);
+    }
+
+    fn selected(&self) -> Option<&InstalledVersion> {
+        self.versions.as_ref().ok()?.get(self.state.selected()?)
+    }
+}
+
+impl Screen for VersionsScreen {
+    fn handle(&mut self, action: &Action) -> ScreenEvent {
+        match action {
+            Action::Select => {
+                if let Some(installed) = self.selected() {
+                    return ScreenEvent::Run(MenuAction::SwitchVersion(installed.version.clone()));
+                }
+            }
+            Action::Delete => {
+                if let Some(installed) = self.selected() {
+                    return ScreenEvent::Run(MenuAction::RemoveVersion(installed.version.clone()));
+                }
+            }
+            Action::Next => self.next(),
+            Action::Previous => self.previous(),
+            _ => return ScreenEvent::Ignored,
+        }
+        ScreenEvent::Handled
+    }
+
+    fn refresh(&mut self) {
+        self.versions = VersionStore::new()
+            .list()
+            .map_err(|e| format!("Failed to list installed versions: {}", e));
+        let selected = match (self.len(), self.state.selected()) {
+            (0, _) => None,
+            (len, Some(selected)) => Some(selected.min(len - 1)),
+            (_, None) => Some(0),
+        };
+        self.state.select(selected);
+    }
+
+    fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = block("Installed versions (Enter: switch, d: remove)", focused);
+        let versions = match &self.versions {
+            Ok(versions) => versions,
+            Err(e) => {
+                frame.render_widget(Paragraph::new(e.as_str()).block(block), area);
+                return;
+            }
+        };
+
+        let items: Vec<ListItem> = if versions.is_empty() {
+            vec![ListItem::new("No versions installed yet")]
+        } else {
+            versions
+                .iter()
+                .map(|installed| {
+                    let marker = if installed.current { " (current)" } else { "" };
+                    ListItem::new(format!("{}{}", installed.version, marker))
+                })
+                .collect()
+        };
+
+        let list = List::new(items).block(block).highlight_symbol(">> ");
+        frame.render_stateful_widget(list, area, &mut list_state(&self.state, focused));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::neovim_nightly::{
+    download::Progress,
+    plan::Plan,
+    release::Release,
+    uninstall::UninstallPlan,
+    update::{FileChecksum, InstallOutcome, ReleaseChoice},
+    version::NvimVersion,
+};
+
+/// The kinds of background work; at most one of each runs at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    CheckUpdates,
+    FetchReleases,
+    Plan,
+    Install,
+    Switch,
+    Remove,
+    Rollback,
+    Uninstall,
+    Authenticate,
+}
+
+impl Task {
+    /// Whether the task changes the installation, after which the screens show
+    /// stale data.
+    pub fn changes_installation(self) -> bool {
+        matches!(
+            self,
+            Task::Install | Task::Switch | Task::Remove | Task::Rollback | Task::Uninstall
+        )
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Task::CheckUpdates => "update check",
+            Task::FetchReleases => "release lookup",
+            Task::Plan => "planning",
+            Task::Install => "install",
+            Task::Switch => "version switch",
+            Task::Remove => "version removal",
+            Task::Rollback => "rollback",
+            Task::Uninstall => "uninstall",
+            Task::Authenticate => "password check",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Where an install gets Neovim from.
+#[derive(Debug, Clone)]
+pub enum InstallSource {
+    /// The selected channel's release; planning settles which asset.
+    Release(Option<Box<ReleaseChoice>>),
+    Source,
+    File(PathBuf, FileChecksum),
+}
+
+/// What runs once its plan has been confirmed.
+#[derive(Clone)]
+pub enum PlannedAction {
+    Install(InstallSource),
+    Rollback,
+    Uninstall(UninstallPlan),
+}
+
+pub enum TaskResult {
+    UpdateCheck(Result<String, String>),
+    Releases(Result<(Vec<Release>, Option<NvimVersion>), String>),
+    Planned(Result<(Plan, PlannedAction), String>),
+    Installed(Result<InstallOutcome, String>),
+    /// What a switch, removal, rollback or uninstall did, or why it failed.
+    Changed(Result<String, String>),
+    /// The sudo password, once sudo accepted it.
+    Authenticated(Result<String, String>),
+    Cancelled,
+}
+
+pub enum TaskEvent {
+    Progress(Progress),
+    Log(String),
+    Finished(Task, Box<TaskResult>),
+}
+
+/// Senders a task reports download progress and build output through.
+pub struct TaskContext {
+    pub progress: mpsc::UnboundedSender<Progress>,
+    pub log: mpsc::UnboundedSender<String>,
+}
+
+/// Runs release lookups, update checks, installs and other changes to the
+/// installation on tokio tasks so the UI keeps drawing, and hands their progress and results back as `TaskEvent`s.
+pub struct TaskRunner {
+    events_tx: mpsc::UnboundedSender<TaskEvent>,
+    events_rx: mpsc::UnboundedReceiver<TaskEvent>,
+    running: Vec<(Task, CancellationToken)>,
+}
+
+impl TaskRunner {
+    pub fn new() -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        Self {
+            events_tx,
+            events_rx,
+            running: Vec::new(),
+        }
+    }
+
+    pub fn is_running(&self, task: Task) -> bool {
+        self.running.iter().any(|(running, _)| *running == task)
+    }
+
+    pub fn is_changing_installation(&self) -> bool {
+        self.running
+            .iter()
+            .any(|(task, _)| task.changes_installation())
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    /// Starts `work` as `task` unless one is already running, and returns
+    /// whether it was started.
+    ///
+    /// Cancelling drops `work` at its next `.await`. Work already handed to a
+    /// blocking thread, such as unpacking into the store, still runs to its
+    /// end, so the store is never left half written, and the install lock stays
+    /// held until it has.
+    pub fn spawn<F, Fut>(&mut self, task: Task, work: F) -> bool
+    where
+        F: FnOnce(TaskContext) -> Fut,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        if self.is_running(task) {
+            return false;
+        }
+
+        let token = CancellationToken::new();
+        self.running.push((task, token.clone()));
+
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        let (log_tx, mut log_rx) = mpsc::unbounded_channel();
+        let work = work(TaskContext {
+            progress: progress_tx,
+            log: log_tx,
+        });
+
+        let events = self.events_tx.clone();
+        tokio::spawn(async move {
+            let forward_events = events.clone();
+            let forward = tokio::spawn(async move {
+                loop {
+                    let event = tokio::select! {
+                        Some(progress) = progress_rx.recv() => TaskEvent::Progress(progress),
+                        Some(line) = log_rx.recv() => TaskEvent::Log(line),
+                        else => break,
+                    };
+                    let _ = forward_events.send(event);
+                }
+            });
+
+            // The context's senders live in `work`, so the forwarder ends once
+            // it has finished or been dropped.
+            let result = tokio::select! {
+                _ = token.cancelled() => TaskResult::Cancelled,
+                result = work => result,
+            };
+            let _ = forward.await;
+            let _ = events.send(TaskEvent::Finished(task, Box::new(result)));
+        });
+
+        true
+    }
+
+    /// Cancels every running task; each still reports back with
+    /// `TaskResult::Cancelled`.
+    pub fn cancel_all(&self) {
+        for (_, token) in &self.running {
+            token.cancel();
+        }
+    }
+
+    /// The next event of any task.
+    pub async fn next(&mut self) -> Option<TaskEvent> {
+        let event = self.events_rx.recv().await?;
+        if let TaskEvent::Finished(task, _) = &event {
+            self.running.retain(|(running, _)| running != task);
+        }
+        Some(event)
+    }
+}
@@ -8,18 +8,33 @@ use ratatui::{
 };
 
 use self::{
-    panes::{footer::FooterPane, header::HeaderPane, menu::MenuPane, Pane},
+    panes::{
+        channel_picker::ChannelPickerPane, file_picker::FilePickerPane, footer::FooterPane,
+        header::HeaderPane, menu::MenuPane, password_prompt::PasswordPromptPane,
+        plan_dialog::PlanDialogPane, uninstall_dialog::UninstallDialogPane, Pane,
+    },
     widget_params::WidgetParams,
 };
 
-use super::{keymaps::KeyBindings, menu::Menu};
+use crate::neovim_nightly::download::Progress;
+
+use super::{
+    channel_picker::ChannelPicker, file_picker::FilePicker, keymaps::KeyBindings, menu::Menu,
+    password_prompt::PasswordPrompt, plan_dialog::PlanDialog, screens::Screen,
+    uninstall_dialog::UninstallDialog,
+};
 
 pub mod panes;
 pub mod widget_params;
 
+/// How many lines of build output are kept for the log pane.
+const BUILD_LOG_LINES: usize = 1000;
+
 pub struct UI {
     pub update_message: String,
     pub show_update_message: bool,
+    pub download_progress: Option<Progress>,
+    pub build_log: Vec<String>,
     pub key_bindings: KeyBindings,
 }
 
@@ -28,6 +43,8 @@ impl UI {
         Self {
             update_message,
             show_update_message: false,
+            download_progress: None,
+            build_log: Vec::new(),
             key_bindings,
         }
     }
@@ -41,6 +58,25 @@ impl UI {
         self.show_update_message = false;
     }
 
+    pub fn set_download_progress(&mut self, progress: Progress) {
+        self.download_progress = Some(progress);
+    }
+
+    pub fn clear_download_progress(&mut self) {
+        self.download_progress = None;
+    }
+
+    pub fn push_build_log(&mut self, line: String) {
+        if self.build_log.len() >= BUILD_LOG_LINES {
+            self.build_log.remove(0);
+        }
+        self.build_log.push(line);
+    }
+
+    pub fn clear_build_log(&mut self) {
+        self.build_log.clear();
+    }
+
     pub fn render_widget(&self, frame: &mut Frame, area: Rect, params: &WidgetParams) {
         let block = Block::default()
             .borders(params.borders)
@@ -69,11 +105,44 @@ impl UI {
         footer_pane.render(self, frame, area, Some(&self.key_bindings as &dyn Any));
     }
 
-    pub fn render_menu(&self, frame: &mut Frame, area: Rect, menu: &Menu) {
-        let menu_pane = MenuPane {};
+    /// The menu with `screen` in the content pane next to it.
+    pub fn render_menu(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        menu: &Menu,
+        screen: &dyn Screen,
+        focused: bool,
+    ) {
+        let menu_pane = MenuPane { screen, focused };
         menu_pane.render(self, frame, area, Some(menu as &dyn Any));
     }
 
+    pub fn render_channel_picker(&self, frame: &mut Frame, area: Rect, picker: &ChannelPicker) {
+        let picker_pane = ChannelPickerPane {};
+        picker_pane.render(self, frame, area, Some(picker as &dyn Any));
+    }
+
+    pub fn render_file_picker(&self, frame: &mut Frame, area: Rect, picker: &FilePicker) {
+        let file_picker_pane = FilePickerPane {};
+        file_picker_pane.render(self, frame, area, Some(picker as &dyn Any));
+    }
+
+    pub fn render_uninstall_dialog(&self, frame: &mut Frame, area: Rect, dialog: &UninstallDialog) {
+        let uninstall_dialog_pane = UninstallDialogPane {};
+        uninstall_dialog_pane.render(self, frame, area, Some(dialog as &dyn Any));
+    }
+
+    pub fn render_plan_dialog(&self, frame: &mut Frame, area: Rect, dialog: &PlanDialog) {
+        let plan_dialog_pane = PlanDialogPane {};
+        plan_dialog_pane.render(self, frame, area, Some(dialog as &dyn Any));
+    }
+
+    pub fn render_password_prompt(&self, frame: &mut Frame, area: Rect, prompt: &PasswordPrompt) {
+        let password_prompt_pane = PasswordPromptPane {};
+        password_prompt_pane.render(self, frame, area, Some(prompt as &dyn Any));
+    }
+
     pub fn render_additional_info(&self, frame: &mut Frame, area: Rect, info_text: &str) {
         let info_params = WidgetParams::new(info_text.to_string())
             .with_borders(Borders::ALL)
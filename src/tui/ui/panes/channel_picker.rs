@@ -0,0 +1,23 @@
+use std::any::Any;
+
+use ratatui::widgets::Clear;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::channel_picker::ChannelPicker;
+use crate::tui::ui::UI;
+
+use super::{popup_area, Pane};
+
+pub struct ChannelPickerPane {}
+
+impl Pane for ChannelPickerPane {
+    fn render(&self, _ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
+        let picker = extra
+            .and_then(|any| any.downcast_ref::<ChannelPicker>())
+            .expect("ChannelPickerPane requires a ChannelPicker reference");
+
+        let popup = popup_area(area, 60, 60);
+        frame.render_widget(Clear, popup);
+        picker.render(frame, popup);
+    }
+}
@@ -0,0 +1,23 @@
+use std::any::Any;
+
+use ratatui::widgets::Clear;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::file_picker::FilePicker;
+use crate::tui::ui::UI;
+
+use super::{popup_area, Pane};
+
+pub struct FilePickerPane {}
+
+impl Pane for FilePickerPane {
+    fn render(&self, _ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
+        let picker = extra
+            .and_then(|any| any.downcast_ref::<FilePicker>())
+            .expect("FilePickerPane requires a FilePicker reference");
+
+        let popup = popup_area(area, 70, 60);
+        frame.render_widget(Clear, popup);
+        picker.render(frame, popup);
+    }
+}
@@ -0,0 +1,23 @@
+use std::any::Any;
+
+use ratatui::style::Color;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::ui::widget_params::WidgetParams;
+use crate::tui::ui::UI;
+
+use super::Pane;
+
+/// Tail of the build output, following the newest line.
+pub struct LogPane {}
+
+impl Pane for LogPane {
+    fn render(&self, ui: &UI, frame: &mut Frame, area: Rect, _extra: Option<&dyn Any>) {
+        let visible = area.height.saturating_sub(2) as usize;
+        let start = ui.build_log.len().saturating_sub(visible);
+        let log_params = WidgetParams::new(ui.build_log[start..].join("\n"))
+            .with_title("Build log".to_string())
+            .with_color(Color::Gray);
+        ui.render_widget(frame, area, &log_params);
+    }
+}
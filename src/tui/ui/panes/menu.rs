@@ -1,18 +1,26 @@
 use std::any::Any;
 
 use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::Color;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, Borders, Gauge};
 use ratatui::{layout::Rect, Frame};
 
+use crate::neovim_nightly::download::Progress;
 use crate::tui::menu::Menu;
-use crate::tui::ui::widget_params::WidgetParams;
+use crate::tui::screens::Screen;
 use crate::tui::ui::UI;
 
-use super::Pane;
+use super::{log::LogPane, Pane};
 
-pub struct MenuPane {}
+/// The menu and, next to it, the content pane showing the selected entry's
+/// screen.
+pub struct MenuPane<'a> {
+    pub screen: &'a dyn Screen,
+    /// Whether the screen is open rather than previewed.
+    pub focused: bool,
+}
 
-impl Pane for MenuPane {
+impl Pane for MenuPane<'_> {
     fn render(&self, ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
         let menu = extra
             .and_then(|any| any.downcast_ref::<Menu>())
@@ -23,10 +31,60 @@ impl Pane for MenuPane {
             .split(area);
 
         menu.render(frame, columns[0]);
-        let content_params =
-            WidgetParams::new("Here is the main content of the application".to_string())
-                .with_title("Content".to_string())
-                .with_color(Color::White);
-        ui.render_widget(frame, columns[1], &content_params);
+
+        let content_area = match &ui.download_progress {
+            Some(progress) => {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)])
+                    .split(columns[1]);
+                render_progress(frame, rows[1], progress);
+                rows[0]
+            }
+            None => columns[1],
+        };
+
+        // The last build's output stays up until a screen is opened.
+        if !ui.build_log.is_empty() && !self.focused {
+            LogPane {}.render(ui, frame, content_area, None);
+            return;
+        }
+
+        self.screen.render(frame, content_area, self.focused);
+    }
+}
+
+fn render_progress(frame: &mut Frame, area: Rect, progress: &Progress) {
+    let total = progress
+        .total
+        .map_or("?".to_string(), |total| format_bytes(total as f64));
+    let eta = progress
+        .eta
+        .map_or("--".to_string(), |eta| format!("{}s", eta.as_secs()));
+    let label = format!(
+        "{} / {}  {}/s  ETA {}",
+        format_bytes(progress.downloaded as f64),
+        total,
+        format_bytes(progress.speed),
+        eta
+    );
+
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title("Download"))
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio(progress.ratio().unwrap_or(0.0))
+        .label(label);
+
+    frame.render_widget(gauge, area);
+}
+
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
+    format!("{:.1} {}", value, UNITS[unit])
 }
@@ -1,13 +1,39 @@
-use std::any::Any;
-use ratatui::layout::Rect;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::Frame;
+use std::any::Any;
 
 use super::UI;
 
+pub mod channel_picker;
+pub mod file_picker;
 pub mod footer;
 pub mod header;
+pub mod log;
 pub mod menu;
+pub mod password_prompt;
+pub mod plan_dialog;
+pub mod uninstall_dialog;
 
 pub trait Pane {
     fn render(&self, ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>);
 }
+
+pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(rows[1])[1]
+}
@@ -0,0 +1,33 @@
+use std::any::Any;
+
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::widgets::Clear;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::password_prompt::PasswordPrompt;
+use crate::tui::ui::UI;
+
+use super::{popup_area, Pane};
+
+pub struct PasswordPromptPane {}
+
+impl Pane for PasswordPromptPane {
+    fn render(&self, _ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
+        let prompt = extra
+            .and_then(|any| any.downcast_ref::<PasswordPrompt>())
+            .expect("PasswordPromptPane requires a PasswordPrompt reference");
+
+        // A single input line, centered vertically.
+        let column = popup_area(area, 50, 100);
+        let popup = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .split(column)[1];
+        frame.render_widget(Clear, popup);
+        prompt.render(frame, popup);
+    }
+}
@@ -0,0 +1,23 @@
+use std::any::Any;
+
+use ratatui::widgets::Clear;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::plan_dialog::PlanDialog;
+use crate::tui::ui::UI;
+
+use super::{popup_area, Pane};
+
+pub struct PlanDialogPane {}
+
+impl Pane for PlanDialogPane {
+    fn render(&self, _ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
+        let dialog = extra
+            .and_then(|any| any.downcast_ref::<PlanDialog>())
+            .expect("PlanDialogPane requires a PlanDialog reference");
+
+        let popup = popup_area(area, 90, 80);
+        frame.render_widget(Clear, popup);
+        dialog.render(frame, popup);
+    }
+}
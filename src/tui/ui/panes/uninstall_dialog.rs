@@ -0,0 +1,23 @@
+use std::any::Any;
+
+use ratatui::widgets::Clear;
+use ratatui::{layout::Rect, Frame};
+
+use crate::tui::ui::UI;
+use crate::tui::uninstall_dialog::UninstallDialog;
+
+use super::{popup_area, Pane};
+
+pub struct UninstallDialogPane {}
+
+impl Pane for UninstallDialogPane {
+    fn render(&self, _ui: &UI, frame: &mut Frame, area: Rect, extra: Option<&dyn Any>) {
+        let dialog = extra
+            .and_then(|any| any.downcast_ref::<UninstallDialog>())
+            .expect("UninstallDialogPane requires an UninstallDialog reference");
+
+        let popup = popup_area(area, 80, 80);
+        frame.render_widget(Clear, popup);
+        dialog.render(frame, popup);
+    }
+}
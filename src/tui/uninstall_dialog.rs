@@ -0,0 +1,117 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::neovim_nightly::{
+    config::Config,
+    uninstall::{NvimDir, UninstallPlan},
+    Result,
+};
+
+use super::list;
+
+/// Lists what an uninstall would remove and lets the user opt in to removing
+/// Neovim's own directories before confirming.
+pub struct UninstallDialog {
+    config: Config,
+    selected: Vec<NvimDir>,
+    plan: UninstallPlan,
+    state: ListState,
+}
+
+impl UninstallDialog {
+    pub fn new(config: &Config) -> Result<UninstallDialog> {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        Ok(UninstallDialog {
+            config: config.clone(),
+            selected: Vec::new(),
+            plan: UninstallPlan::new(config, &[])?,
+            state,
+        })
+    }
+
+    /// Toggle rows for each Neovim directory, then the confirm row.
+    fn len(&self) -> usize {
+        NvimDir::ALL.len() + 1
+    }
+
+    pub fn next(&mut self) {
+        let len = self.len();
+        list::select_next(&mut self.state, len);
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.len();
+        list::select_previous(&mut self.state, len);
+    }
+
+    /// Toggles the selected directory and returns `None`, or returns the plan
+    /// when the confirm row is selected.
+    pub fn select(&mut self) -> Result<Option<&UninstallPlan>> {
+        let selected = self.state.selected().unwrap_or(0);
+        let Some(dir) = NvimDir::ALL.get(selected) else {
+            return Ok(Some(&self.plan));
+        };
+
+        if let Some(index) = self.selected.iter().position(|chosen| chosen == dir) {
+            self.selected.remove(index);
+        } else {
+            self.selected.push(*dir);
+        }
+        self.plan = UninstallPlan::new(&self.config, &self.selected)?;
+        Ok(None)
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(self.len() as u16 + 2),
+            ])
+            .split(area);
+
+        let description = if self.plan.is_empty() {
+            "Nothing to uninstall".to_string()
+        } else {
+            self.plan.plan().describe().join("\n")
+        };
+        let plan = Paragraph::new(description).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Uninstall will"),
+        );
+        frame.render_widget(plan, rows[0]);
+
+        let mut items: Vec<ListItem> = NvimDir::ALL
+            .iter()
+            .map(|dir| {
+                let mark = if self.selected.contains(dir) {
+                    "x"
+                } else {
+                    " "
+                };
+                ListItem::new(format!(
+                    "[{}] Also remove Neovim {} ({})",
+                    mark,
+                    dir,
+                    dir.path().display()
+                ))
+            })
+            .collect();
+        items.push(ListItem::new("Uninstall"));
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Options (Enter: toggle / confirm, Esc: cancel)"),
+            )
+            .highlight_symbol(">> ");
+
+        frame.render_stateful_widget(list, rows[1], &mut self.state.clone());
+    }
+}